@@ -0,0 +1,299 @@
+//! A [`TicketStore`] that survives restarts.
+//!
+//! Every change is appended to a write-ahead log before it is applied in memory, and the log
+//! is replayed when the store is opened. Every so often the whole store is written to a
+//! snapshot and the log is emptied, to keep replays short.
+//!
+//! Both files are made of frames: the payload length and its CRC32, as little-endian `u32`s,
+//! followed by the JSON payload. A crash halfway through an append leaves a truncated or
+//! mismatching frame at the end of the log: it is dropped on the next open. A bad frame
+//! anywhere else is reported as corruption rather than silently skipped.
+use crate::data::{Status, Ticket, TicketDraft};
+use crate::store::{TicketId, TicketStore};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_FILE: &str = "tickets.wal";
+const SNAPSHOT_FILE: &str = "tickets.snapshot";
+const FRAME_HEADER_LENGTH: usize = 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("I/O error on the ticket storage")]
+    Io(#[from] io::Error),
+    #[error("{} is corrupted at byte {offset}", path.display())]
+    Corrupted { path: PathBuf, offset: usize },
+    #[error("{} holds a record that cannot be decoded", path.display())]
+    InvalidRecord {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// A change to the store, as written to the log.
+#[derive(Serialize, Deserialize)]
+enum Record {
+    /// Insert or replace a ticket.
+    Put(Ticket),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    tickets: Vec<Ticket>,
+}
+
+pub struct DurableStore {
+    store: TicketStore,
+    dir: PathBuf,
+    log: File,
+    /// The length of the log up to its last complete record.
+    log_length: u64,
+    since_snapshot: usize,
+    snapshot_every: usize,
+}
+
+impl DurableStore {
+    /// How many records are appended to the log, by default, before taking a snapshot.
+    pub const DEFAULT_SNAPSHOT_EVERY: usize = 1_000;
+
+    /// Open the store kept in `dir`, creating it if needed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut store = TicketStore::new();
+        let snapshot_path = dir.join(SNAPSHOT_FILE);
+        if snapshot_path.exists() {
+            let bytes = fs::read(&snapshot_path)?;
+            // Snapshots are renamed into place once complete, so any damage is corruption,
+            // including bytes after the frame.
+            let (frames, valid_length) = read_frames(&bytes);
+            let ([payload], true) = (&frames[..], valid_length == bytes.len()) else {
+                return Err(StorageError::Corrupted {
+                    path: snapshot_path,
+                    offset: valid_length,
+                });
+            };
+            let snapshot: Snapshot = decode(payload, &snapshot_path)?;
+            for ticket in snapshot.tickets {
+                store.put(ticket);
+            }
+        }
+
+        let log_path = dir.join(LOG_FILE);
+        let bytes = match fs::read(&log_path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let (frames, valid_length) = read_frames(&bytes);
+        if valid_length < bytes.len() && !is_torn_tail(&bytes[valid_length..]) {
+            return Err(StorageError::Corrupted {
+                path: log_path,
+                offset: valid_length,
+            });
+        }
+        for payload in &frames {
+            match decode(payload, &log_path)? {
+                Record::Put(ticket) => store.put(ticket),
+            }
+        }
+
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+        // Drop the torn tail, so that new records are appended right after the last good one.
+        log.set_len(valid_length as u64)?;
+
+        Ok(Self {
+            store,
+            dir,
+            log,
+            log_length: valid_length as u64,
+            since_snapshot: frames.len(),
+            snapshot_every: Self::DEFAULT_SNAPSHOT_EVERY,
+        })
+    }
+
+    /// Take a snapshot after this many records have been appended to the log.
+    pub fn snapshot_every(mut self, records: usize) -> Self {
+        self.snapshot_every = records.max(1);
+        self
+    }
+
+    pub fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, StorageError> {
+        let ticket = Ticket {
+            id: self.store.next_id(),
+            title: draft.title,
+            description: draft.description,
+            status: Status::ToDo,
+        };
+        let id = ticket.id;
+        self.put(ticket)?;
+        Ok(id)
+    }
+
+    /// Insert or replace a ticket, e.g. after applying a patch to it.
+    ///
+    /// The change is durable once this returns `Ok`. Failing to take a snapshot afterwards
+    /// doesn't fail the change: the snapshot is retried on the next one.
+    pub fn put(&mut self, ticket: Ticket) -> Result<(), StorageError> {
+        let record = Record::Put(ticket);
+        let frame = frame(&record);
+        append(&self.log, &self.log, self.log_length, &frame)?;
+        self.log_length += frame.len() as u64;
+        let Record::Put(ticket) = record;
+        self.store.put(ticket);
+
+        self.since_snapshot += 1;
+        if self.since_snapshot >= self.snapshot_every {
+            // The record is already in the log, so the store is consistent either way.
+            let _ = self.snapshot();
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.store.get(id)
+    }
+
+    /// Write the whole store to a snapshot, then empty the log.
+    pub fn snapshot(&mut self) -> Result<(), StorageError> {
+        let snapshot = Snapshot {
+            tickets: self.store.tickets().cloned().collect(),
+        };
+        let temporary = self.dir.join(format!("{SNAPSHOT_FILE}.tmp"));
+        let mut file = File::create(&temporary)?;
+        file.write_all(&frame(&snapshot))?;
+        file.sync_all()?;
+        fs::rename(&temporary, self.dir.join(SNAPSHOT_FILE))?;
+        // Make the rename itself durable before dropping the records it replaces.
+        // Replaying records that made it into the snapshot is harmless: `Put` is idempotent.
+        // Only Unix lets a directory be opened, and synced, like a file.
+        #[cfg(unix)]
+        File::open(&self.dir)?.sync_all()?;
+        self.log.set_len(0)?;
+        self.log.sync_all()?;
+        self.log_length = 0;
+        self.since_snapshot = 0;
+        Ok(())
+    }
+}
+
+/// Append `frame` to `log`, which is `length` bytes long, by writing it to `writer`.
+///
+/// If the append fails, the log is cut back to `length`: a torn frame left behind would end
+/// up in the middle of the log after the next append, and be reported as corruption.
+fn append(log: &File, mut writer: impl Write, length: u64, frame: &[u8]) -> io::Result<()> {
+    let result = writer.write_all(frame).and_then(|()| log.sync_data());
+    if result.is_err() {
+        log.set_len(length)?;
+        (&*log).seek(SeekFrom::Start(length))?;
+    }
+    result
+}
+
+fn frame(value: &impl Serialize) -> Vec<u8> {
+    let payload = serde_json::to_vec(value).expect("tickets can always be serialized");
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LENGTH + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    frame.extend_from_slice(&payload);
+    frame
+}
+
+/// Split `bytes` into frame payloads, stopping at the first invalid frame.
+///
+/// Also returns the length of the valid prefix.
+fn read_frames(bytes: &[u8]) -> (Vec<&[u8]>, usize) {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while let Some(payload) = read_frame(&bytes[offset..]) {
+        frames.push(payload);
+        offset += FRAME_HEADER_LENGTH + payload.len();
+    }
+    (frames, offset)
+}
+
+fn read_frame(bytes: &[u8]) -> Option<&[u8]> {
+    let header = bytes.get(..FRAME_HEADER_LENGTH)?;
+    let length = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+    let checksum = u32::from_le_bytes(header[4..].try_into().unwrap());
+    let payload = bytes.get(FRAME_HEADER_LENGTH..FRAME_HEADER_LENGTH + length)?;
+    (crc32fast::hash(payload) == checksum).then_some(payload)
+}
+
+/// Whether an invalid frame is the last thing in the log, as left by an interrupted append.
+fn is_torn_tail(bytes: &[u8]) -> bool {
+    let Some(header) = bytes.get(..FRAME_HEADER_LENGTH) else {
+        return true;
+    };
+    let length = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+    bytes.len() <= FRAME_HEADER_LENGTH + length
+}
+
+fn decode<'a, T: Deserialize<'a>>(payload: &'a [u8], path: &Path) -> Result<T, StorageError> {
+    serde_json::from_slice(payload).map_err(|source| StorageError::InvalidRecord {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    /// Writes the first `budget` bytes it is given, then fails as a full disk would.
+    struct Failing<'a> {
+        file: &'a File,
+        budget: usize,
+    }
+
+    impl Write for Failing<'_> {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            if self.budget == 0 {
+                return Err(io::ErrorKind::StorageFull.into());
+            }
+            let written = self.file.write(&bytes[..bytes.len().min(self.budget)])?;
+            self.budget -= written;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.file.flush()
+        }
+    }
+
+    fn draft() -> TicketDraft {
+        TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+        }
+    }
+
+    #[test]
+    fn a_failed_append_is_cut_from_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path()).unwrap();
+        let first = store.add_ticket(draft()).unwrap();
+
+        let ticket = store.get(first).unwrap().clone();
+        let frame = frame(&Record::Put(ticket));
+        let failing = Failing {
+            file: &store.log,
+            budget: 5,
+        };
+        assert!(append(&store.log, failing, store.log_length, &frame).is_err());
+
+        let second = store.add_ticket(draft()).unwrap();
+        drop(store);
+        let store = DurableStore::open(dir.path()).unwrap();
+        assert!(store.get(first).is_some());
+        assert!(store.get(second).is_some());
+    }
+}
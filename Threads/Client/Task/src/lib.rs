@@ -1,47 +1,219 @@
 use crate::data::{Ticket, TicketDraft};
+use crate::durable::{DurableStore, StorageError};
 use crate::store::{TicketId, TicketStore};
-use std::sync::mpsc::{Receiver, Sender};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 pub mod data;
+pub mod durable;
 pub mod store;
 
 #[derive(Clone)]
-// TODO: flesh out the client implementation.
 pub struct TicketStoreClient {
     sender: Sender<Command>,
+    shutting_down: Arc<AtomicBool>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The server was asked to shut down, and doesn't take new commands anymore.
+    #[error("The ticket server is shutting down")]
+    ShuttingDown,
+    /// The server has stopped, or crashed while handling the command.
+    #[error("The ticket server is gone")]
+    ServerGone,
+    #[error(transparent)]
+    Storage(#[from] StorageError),
 }
 
 impl TicketStoreClient {
-    // Feel free to panic on all errors, for simplicity.
-    pub fn insert(&self, draft: TicketDraft) -> TicketId {
-        /* TODO */
+    /// Fails if the store couldn't write the ticket to storage.
+    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, ClientError> {
+        self.request(|response_channel| Command::Insert {
+            draft,
+            response_channel,
+        })
     }
 
-    pub fn get(&self, id: TicketId) -> Option<Ticket> {
-        /* TODO */
+    pub fn get(&self, id: TicketId) -> Result<Option<Ticket>, ClientError> {
+        self.request(|response_channel| Command::Get {
+            id,
+            response_channel,
+        })
+    }
+
+    /// Send the command built by `command` and wait for the server's answer.
+    fn request<T>(
+        &self,
+        command: impl FnOnce(Sender<Result<T, ClientError>>) -> Command,
+    ) -> Result<T, ClientError> {
+        if self.shutting_down.load(Ordering::Acquire) {
+            return Err(ClientError::ShuttingDown);
+        }
+        let (response_sender, response_receiver) = std::sync::mpsc::channel();
+        self.sender
+            .send(command(response_sender))
+            .map_err(|_| ClientError::ServerGone)?;
+        response_receiver
+            .recv()
+            .map_err(|_| ClientError::ServerGone)?
     }
 }
 
-pub fn launch() -> TicketStoreClient {
+/// A running server.
+///
+/// Dropping the handle leaves the server running until all its clients are dropped:
+/// use [`shutdown`](Self::shutdown) to stop it right away.
+pub struct ServerHandle {
+    sender: Sender<Command>,
+    shutting_down: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ShutdownError {
+    #[error("The ticket server didn't shut down in time")]
+    Timeout,
+    #[error("The ticket server crashed")]
+    Crashed,
+    #[error("Failed to flush the ticket store")]
+    Flush(#[from] StorageError),
+}
+
+impl ServerHandle {
+    pub fn client(&self) -> TicketStoreClient {
+        TicketStoreClient {
+            sender: self.sender.clone(),
+            shutting_down: Arc::clone(&self.shutting_down),
+        }
+    }
+
+    /// Stop the server, once it has handled the commands sent before this call.
+    ///
+    /// The commands sent afterwards fail with [`ClientError::ShuttingDown`], or with
+    /// [`ClientError::ServerGone`] once the server has stopped. The store is flushed before
+    /// the server thread is joined, unless that takes longer than `timeout`: the server is
+    /// then left to finish on its own.
+    pub fn shutdown(self, timeout: Duration) -> Result<(), ShutdownError> {
+        self.shutting_down.store(true, Ordering::Release);
+        let (response_sender, response_receiver) = std::sync::mpsc::channel();
+        let _ = self.sender.send(Command::Shutdown {
+            response_channel: response_sender,
+        });
+        match response_receiver.recv_timeout(timeout) {
+            Ok(flushed) => {
+                self.thread.join().map_err(|_| ShutdownError::Crashed)?;
+                Ok(flushed?)
+            }
+            Err(RecvTimeoutError::Timeout) => Err(ShutdownError::Timeout),
+            // The server panicked, before or while handling the shutdown.
+            Err(RecvTimeoutError::Disconnected) => {
+                let _ = self.thread.join();
+                Err(ShutdownError::Crashed)
+            }
+        }
+    }
+}
+
+pub fn launch() -> ServerHandle {
+    spawn(TicketStore::new())
+}
+
+/// Launch a server whose tickets are kept in `dir`, and survive restarts.
+///
+/// The store is opened, and the log replayed, before this returns.
+pub fn launch_durable(dir: impl AsRef<Path>) -> Result<ServerHandle, StorageError> {
+    Ok(spawn(DurableStore::open(dir)?))
+}
+
+fn spawn(store: impl Backend + Send + 'static) -> ServerHandle {
     let (sender, receiver) = std::sync::mpsc::channel();
-    std::thread::spawn(move || server(receiver));
-    /* TODO */
+    let thread = std::thread::spawn(move || server(receiver, store));
+    ServerHandle {
+        sender,
+        shutting_down: Arc::default(),
+        thread,
+    }
+}
+
+/// The operations the server dispatches to its store.
+trait Backend {
+    fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, StorageError>;
+    fn get(&self, id: TicketId) -> Option<&Ticket>;
+    /// Make sure that everything the store holds has reached its storage.
+    fn flush(&mut self) -> Result<(), StorageError>;
+}
+
+impl Backend for TicketStore {
+    fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, StorageError> {
+        Ok(self.add_ticket(draft))
+    }
+
+    fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.get(id)
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+impl Backend for DurableStore {
+    fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, StorageError> {
+        self.add_ticket(draft)
+    }
+
+    fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.get(id)
+    }
+
+    /// Records are synced as they are appended: a snapshot spares the next open from
+    /// replaying them.
+    fn flush(&mut self) -> Result<(), StorageError> {
+        self.snapshot()
+    }
 }
 
 // No longer public! This becomes an internal detail of the library now.
 enum Command {
     Insert {
         draft: TicketDraft,
-        response_channel: Sender<TicketId>,
+        response_channel: Sender<Result<TicketId, ClientError>>,
     },
     Get {
         id: TicketId,
-        response_channel: Sender<Option<Ticket>>,
+        response_channel: Sender<Result<Option<Ticket>, ClientError>>,
     },
+    Shutdown {
+        response_channel: Sender<Result<(), StorageError>>,
+    },
+}
+
+impl Command {
+    /// Answer the command with `error`, without handling it.
+    fn reject(self, error: ClientError) {
+        match self {
+            Command::Insert {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(error));
+            }
+            Command::Get {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(error));
+            }
+            // There is a single handle, so there is nobody to send a second shutdown.
+            Command::Shutdown { .. } => {}
+        }
+    }
 }
 
-fn server(receiver: Receiver<Command>) {
-    let mut store = TicketStore::new();
+fn server(receiver: Receiver<Command>, mut store: impl Backend) {
     loop {
         match receiver.recv() {
             Ok(Command::Insert {
@@ -49,14 +221,22 @@ fn server(receiver: Receiver<Command>) {
                 response_channel,
             }) => {
                 let id = store.add_ticket(draft);
-                let _ = response_channel.send(id);
+                let _ = response_channel.send(id.map_err(ClientError::from));
             }
             Ok(Command::Get {
                 id,
                 response_channel,
             }) => {
                 let ticket = store.get(id);
-                let _ = response_channel.send(ticket.cloned());
+                let _ = response_channel.send(Ok(ticket.cloned()));
+            }
+            Ok(Command::Shutdown { response_channel }) => {
+                // Clients may have sent commands before they saw the shutdown.
+                for command in receiver.try_iter() {
+                    command.reject(ClientError::ShuttingDown);
+                }
+                let _ = response_channel.send(store.flush());
+                break;
             }
             Err(_) => {
                 // There are no more senders, so we can safely break
@@ -1,10 +1,9 @@
 use crate::data::{Status, Ticket, TicketDraft};
 use std::collections::BTreeMap;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct TicketId(u64);
+pub use ticket_fields::TicketId;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TicketStore {
     tickets: BTreeMap<TicketId, Ticket>,
     counter: u64,
@@ -19,7 +18,7 @@ impl TicketStore {
     }
 
     pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
-        let id = TicketId(self.counter);
+        let id = TicketId::new(self.counter);
         self.counter += 1;
         let ticket = Ticket {
             id,
@@ -34,4 +33,19 @@ impl TicketStore {
     pub fn get(&self, id: TicketId) -> Option<&Ticket> {
         self.tickets.get(&id)
     }
+
+    /// The id the next call to `add_ticket` will hand out.
+    pub(crate) fn next_id(&self) -> TicketId {
+        TicketId::new(self.counter)
+    }
+
+    /// Store `ticket` as is, replacing any ticket with the same id.
+    pub(crate) fn put(&mut self, ticket: Ticket) {
+        self.counter = self.counter.max(ticket.id.number() + 1);
+        self.tickets.insert(ticket.id, ticket);
+    }
+
+    pub(crate) fn tickets(&self) -> impl Iterator<Item = &Ticket> {
+        self.tickets.values()
+    }
 }
@@ -0,0 +1,237 @@
+use std::time::Duration;
+use task_client::data::{Status, TicketDraft};
+use task_client::{launch, ClientError};
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+#[test]
+fn works() {
+    let client = launch().client();
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    };
+    let client2 = client.clone();
+    let ticket_id = client.insert(draft.clone()).unwrap();
+    let ticket = client2.get(ticket_id).unwrap().unwrap();
+    assert_eq!(ticket_id, ticket.id);
+    assert_eq!(ticket.status, Status::ToDo);
+    assert_eq!(ticket.title, draft.title);
+    assert_eq!(ticket.description, draft.description);
+}
+
+#[test]
+fn shuts_down() {
+    let server = launch();
+    let client = server.client();
+    let id = client
+        .insert(TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+        })
+        .unwrap();
+    server.shutdown(Duration::from_secs(5)).unwrap();
+
+    assert!(matches!(client.get(id), Err(ClientError::ShuttingDown)));
+}
+
+#[test]
+fn answers_every_command_around_a_shutdown() {
+    let server = launch();
+    let writers: Vec<_> = (0..4)
+        .map(|_| {
+            let client = server.client();
+            std::thread::spawn(move || loop {
+                let draft = TicketDraft {
+                    title: ticket_title(),
+                    description: ticket_description(),
+                };
+                if let Err(error) = client.insert(draft) {
+                    return error;
+                }
+            })
+        })
+        .collect();
+    std::thread::sleep(Duration::from_millis(10));
+    server.shutdown(Duration::from_secs(5)).unwrap();
+
+    // No writer is left waiting for an answer that never comes.
+    for writer in writers {
+        assert!(matches!(
+            writer.join().unwrap(),
+            ClientError::ShuttingDown | ClientError::ServerGone
+        ));
+    }
+}
+
+mod durable {
+    use common::fixtures::{self, SizeProfile};
+    use std::fs::{self, OpenOptions};
+    use std::time::Duration;
+    use task_client::data::{Status, TicketDraft};
+    use task_client::durable::{DurableStore, StorageError};
+    use task_client::launch_durable;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    fn draft() -> TicketDraft {
+        TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+        }
+    }
+
+    #[test]
+    fn survives_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let server = launch_durable(dir.path()).unwrap();
+        let first = server.client().insert(draft()).unwrap();
+        server.shutdown(Duration::from_secs(5)).unwrap();
+
+        let client = launch_durable(dir.path()).unwrap().client();
+        assert_eq!(client.get(first).unwrap().unwrap().title, ticket_title());
+        let second = client.insert(draft()).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn reloads_the_fixture_dataset() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixtures = fixtures::tickets(SizeProfile::Medium);
+
+        let mut store = DurableStore::open(dir.path()).unwrap();
+        let ids: Vec<_> = fixtures
+            .iter()
+            .map(|fixture| {
+                store
+                    .add_ticket(TicketDraft {
+                        title: fixture.title.clone().try_into().unwrap(),
+                        description: fixture.description.clone().try_into().unwrap(),
+                    })
+                    .unwrap()
+            })
+            .collect();
+        drop(store);
+
+        let store = DurableStore::open(dir.path()).unwrap();
+        for (id, fixture) in ids.into_iter().zip(&fixtures) {
+            let ticket = store.get(id).unwrap();
+            assert_eq!(ticket.title.as_str(), fixture.title);
+            assert_eq!(ticket.description.as_str(), fixture.description);
+        }
+    }
+
+    #[test]
+    fn replays_log_on_top_of_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path()).unwrap().snapshot_every(3);
+        let ids: Vec<_> = (0..5).map(|_| store.add_ticket(draft()).unwrap()).collect();
+        let mut ticket = store.get(ids[0]).unwrap().clone();
+        ticket.status = Status::Done;
+        store.put(ticket).unwrap();
+        drop(store);
+
+        assert!(dir.path().join("tickets.snapshot").exists());
+        let store = DurableStore::open(dir.path()).unwrap();
+        assert_eq!(store.get(ids[0]).unwrap().status, Status::Done);
+        for id in &ids[1..] {
+            assert_eq!(store.get(*id).unwrap().status, Status::ToDo);
+        }
+    }
+
+    #[test]
+    fn flushes_on_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = launch_durable(dir.path()).unwrap();
+        let id = server.client().insert(draft()).unwrap();
+        server.shutdown(Duration::from_secs(5)).unwrap();
+
+        // Everything made it into the snapshot.
+        assert_eq!(
+            fs::metadata(dir.path().join("tickets.wal")).unwrap().len(),
+            0
+        );
+        let store = DurableStore::open(dir.path()).unwrap();
+        assert!(store.get(id).is_some());
+    }
+
+    #[test]
+    fn drops_a_torn_tail_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path()).unwrap();
+        let first = store.add_ticket(draft()).unwrap();
+        let second = store.add_ticket(draft()).unwrap();
+        drop(store);
+
+        // Cut the last record short, as a crash halfway through the write would.
+        let log = dir.path().join("tickets.wal");
+        let length = fs::metadata(&log).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&log)
+            .unwrap()
+            .set_len(length - 5)
+            .unwrap();
+
+        let mut store = DurableStore::open(dir.path()).unwrap();
+        assert!(store.get(first).is_some());
+        assert!(store.get(second).is_none());
+        assert_eq!(store.add_ticket(draft()).unwrap(), second);
+        drop(store);
+        let store = DurableStore::open(dir.path()).unwrap();
+        assert!(store.get(second).is_some());
+    }
+
+    #[test]
+    fn rejects_bytes_after_the_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path()).unwrap();
+        store.add_ticket(draft()).unwrap();
+        store.snapshot().unwrap();
+        drop(store);
+
+        let snapshot = dir.path().join("tickets.snapshot");
+        let mut bytes = fs::read(&snapshot).unwrap();
+        let length = bytes.len();
+        bytes.extend_from_slice(b"junk");
+        fs::write(&snapshot, &bytes).unwrap();
+
+        assert!(matches!(
+            DurableStore::open(dir.path()),
+            Err(StorageError::Corrupted { offset, .. }) if offset == length
+        ));
+    }
+
+    #[test]
+    fn a_failed_snapshot_keeps_the_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path()).unwrap().snapshot_every(1);
+        // The snapshot can't be written while a directory is in the way.
+        let blocker = dir.path().join("tickets.snapshot.tmp");
+        fs::create_dir(&blocker).unwrap();
+
+        let id = store.add_ticket(draft()).unwrap();
+        drop(store);
+        fs::remove_dir(&blocker).unwrap();
+        assert!(DurableStore::open(dir.path()).unwrap().get(id).is_some());
+    }
+
+    #[test]
+    fn reports_corruption_before_the_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path()).unwrap();
+        store.add_ticket(draft()).unwrap();
+        store.add_ticket(draft()).unwrap();
+        drop(store);
+
+        // Flip a byte in the payload of the first record.
+        let log = dir.path().join("tickets.wal");
+        let mut bytes = fs::read(&log).unwrap();
+        bytes[10] ^= 0xFF;
+        fs::write(&log, &bytes).unwrap();
+
+        assert!(matches!(
+            DurableStore::open(dir.path()),
+            Err(StorageError::Corrupted { offset: 0, .. })
+        ));
+    }
+}
@@ -1,5 +1,13 @@
+use crate::history::Change;
 use crate::store::TicketId;
-use ticket_fields::{TicketDescription, TicketTitle};
+use crate::users::UnknownUserError;
+use crate::workflow::TransitionError;
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::num::NonZeroUsize;
+use std::time::SystemTime;
+use ticket_fields::{InlineStr, Label, TicketDescription, TicketTitle, UserId};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Ticket {
@@ -7,12 +15,36 @@ pub struct Ticket {
     pub title: TicketTitle,
     pub description: TicketDescription,
     pub status: Status,
+    pub reporter: Option<UserId>,
+    pub assignee: Option<UserId>,
+    pub watchers: BTreeSet<UserId>,
+    pub labels: BTreeSet<Label>,
+    pub priority: Priority,
+    pub due: Option<SystemTime>,
+    /// Starts at 0, and is bumped by every update that changes the ticket.
+    pub version: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TicketDraft {
     pub title: TicketTitle,
     pub description: TicketDescription,
+    pub reporter: Option<UserId>,
+    pub assignee: Option<UserId>,
+    pub watchers: BTreeSet<UserId>,
+    pub labels: BTreeSet<Label>,
+    pub priority: Priority,
+    pub due: Option<SystemTime>,
+}
+
+impl TicketDraft {
+    /// The users the draft refers to, who must all be known to the store.
+    pub fn users(&self) -> impl Iterator<Item = &UserId> {
+        self.reporter
+            .iter()
+            .chain(&self.assignee)
+            .chain(&self.watchers)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -21,11 +53,388 @@ pub struct TicketPatch {
     pub title: Option<TicketTitle>,
     pub description: Option<TicketDescription>,
     pub status: Option<Status>,
+    /// If set, replaces the assignee: `Some(None)` unassigns the ticket.
+    pub assignee: Option<Option<UserId>>,
+    /// If set, replaces the watchers of the ticket.
+    pub watchers: Option<BTreeSet<UserId>>,
+    pub add_labels: BTreeSet<Label>,
+    /// Applied after `add_labels`: a label in both is removed.
+    pub remove_labels: BTreeSet<Label>,
+    pub priority: Option<Priority>,
+    /// If set, replaces the due date: `Some(None)` clears it.
+    pub due: Option<Option<SystemTime>>,
+    /// If set, the patch is only applied if the ticket is still at this version.
+    pub expected_version: Option<u64>,
+}
+
+impl TicketPatch {
+    /// The users the patch refers to, who must all be known to the store.
+    pub fn users(&self) -> impl Iterator<Item = &UserId> {
+        self.assignee
+            .iter()
+            .flatten()
+            .chain(self.watchers.iter().flatten())
+    }
+
+    /// Fail if the patch expects `ticket` to be at another version.
+    pub fn check_version(&self, ticket: &Ticket) -> Result<(), ConflictError> {
+        match self.expected_version {
+            Some(expected) if expected != ticket.version => Err(ConflictError {
+                id: ticket.id,
+                expected,
+                current: ticket.version,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Overwrite the fields of `ticket` that are set in the patch, returning those that
+    /// actually changed.
+    pub fn apply(self, ticket: &mut Ticket) -> Vec<Change> {
+        let mut changes = Vec::new();
+        if let Some(title) = self.title.filter(|title| *title != ticket.title) {
+            let old = std::mem::replace(&mut ticket.title, title.clone());
+            changes.push(Change::Title { old, new: title });
+        }
+        if let Some(description) = self
+            .description
+            .filter(|description| *description != ticket.description)
+        {
+            let old = std::mem::replace(&mut ticket.description, description.clone());
+            changes.push(Change::Description {
+                old,
+                new: description,
+            });
+        }
+        if let Some(status) = self.status.filter(|status| *status != ticket.status) {
+            let old = std::mem::replace(&mut ticket.status, status);
+            changes.push(Change::Status { old, new: status });
+        }
+        if let Some(assignee) = self
+            .assignee
+            .filter(|assignee| *assignee != ticket.assignee)
+        {
+            let old = std::mem::replace(&mut ticket.assignee, assignee.clone());
+            changes.push(Change::Assignee { old, new: assignee });
+        }
+        if let Some(watchers) = self
+            .watchers
+            .filter(|watchers| *watchers != ticket.watchers)
+        {
+            let old = std::mem::replace(&mut ticket.watchers, watchers.clone());
+            changes.push(Change::Watchers { old, new: watchers });
+        }
+        let mut labels = ticket.labels.clone();
+        labels.extend(self.add_labels);
+        labels.retain(|label| !self.remove_labels.contains(label));
+        if labels != ticket.labels {
+            let old = std::mem::replace(&mut ticket.labels, labels.clone());
+            changes.push(Change::Labels { old, new: labels });
+        }
+        if let Some(priority) = self
+            .priority
+            .filter(|priority| *priority != ticket.priority)
+        {
+            let old = std::mem::replace(&mut ticket.priority, priority);
+            changes.push(Change::Priority { old, new: priority });
+        }
+        if let Some(due) = self.due.filter(|due| *due != ticket.due) {
+            let old = std::mem::replace(&mut ticket.due, due);
+            changes.push(Change::Due { old, new: due });
+        }
+        changes
+    }
+}
+
+/// Which tickets to list, by assignee.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssigneeFilter {
+    Unassigned,
+    User(UserId),
+}
+
+impl AssigneeFilter {
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        match self {
+            AssigneeFilter::Unassigned => ticket.assignee.is_none(),
+            AssigneeFilter::User(user) => ticket.assignee.as_ref() == Some(user),
+        }
+    }
+}
+
+/// Which tickets to list: a ticket must match every criterion that is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TicketFilter {
+    pub assignee: Option<AssigneeFilter>,
+    /// The lowest priority to include.
+    pub priority: Option<Priority>,
+    pub label: Option<Label>,
+    /// Only include the tickets that are overdue at this time.
+    pub overdue_at: Option<SystemTime>,
+}
+
+impl TicketFilter {
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        self.assignee
+            .as_ref()
+            .is_none_or(|assignee| assignee.matches(ticket))
+            && self
+                .priority
+                .is_none_or(|priority| ticket.priority >= priority)
+            && self
+                .label
+                .as_ref()
+                .is_none_or(|label| ticket.labels.contains(label))
+            && self.overdue_at.is_none_or(|now| ticket.is_overdue(now))
+    }
+}
+
+/// The order in which to list tickets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Id,
+    /// Highest priority first, then soonest due, then by id.
+    Priority,
+}
+
+impl SortBy {
+    pub fn sort(self, tickets: &mut [impl Borrow<Ticket>]) {
+        match self {
+            SortBy::Id => tickets.sort_by_key(|ticket| ticket.borrow().id),
+            SortBy::Priority => tickets.sort_by_key(|ticket| {
+                let ticket = ticket.borrow();
+                (
+                    Reverse(ticket.priority),
+                    ticket.due.is_none(),
+                    ticket.due,
+                    ticket.id,
+                )
+            }),
+        }
+    }
+}
+
+impl Ticket {
+    /// Whether the ticket is still open past its due date.
+    pub fn is_overdue(&self, now: SystemTime) -> bool {
+        !self.status.is_closed() && self.due.is_some_and(|due| due < now)
+    }
+}
+
+/// Which slice of a listing to return: up to `limit` tickets, starting after `after`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Page {
+    pub after: Option<TicketId>,
+    pub limit: NonZeroUsize,
+}
+
+impl Page {
+    pub fn first(limit: NonZeroUsize) -> Self {
+        Self { after: None, limit }
+    }
+}
+
+/// A slice of a listing, by id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TicketPage {
+    pub tickets: Vec<Ticket>,
+    /// The page that comes after this one, if there are more tickets.
+    pub next: Option<Page>,
+}
+
+impl TicketPage {
+    /// Build a page out of the tickets that come after `page.after`, by id.
+    ///
+    /// `tickets` may hold more than `page.limit` tickets: only the extra one is read, to tell
+    /// whether there is a next page.
+    pub fn collect(page: Page, tickets: impl IntoIterator<Item = Ticket>) -> Self {
+        let mut tickets: Vec<_> = tickets.into_iter().take(page.limit.get() + 1).collect();
+        let next = (tickets.len() > page.limit.get()).then(|| {
+            tickets.truncate(page.limit.get());
+            Page {
+                after: tickets.last().map(|ticket| ticket.id),
+                limit: page.limit,
+            }
+        });
+        Self { tickets, next }
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[error("There is no ticket with id {0}")]
+pub struct NotFoundError(pub TicketId);
+
+/// A patch expected the ticket to be at a version it has moved past.
+///
+/// The caller should fetch the ticket again and decide whether to retry. Over HTTP, this is a
+/// `412 Precondition Failed` if the expected version came from an `If-Match` header, and a
+/// `409 Conflict` otherwise.
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[error("Ticket {id} is at version {current}, but the patch expected version {expected}")]
+pub struct ConflictError {
+    pub id: TicketId,
+    pub expected: u64,
+    pub current: u64,
+}
+
+/// Why a patch was not applied.
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    #[error(transparent)]
+    NotFound(#[from] NotFoundError),
+    #[error(transparent)]
+    Conflict(#[from] ConflictError),
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
+    #[error(transparent)]
+    UnknownUser(#[from] UnknownUserError),
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+/// Which status changes are allowed is up to the store's [`Workflow`](crate::workflow::Workflow).
+///
+/// The built-in statuses are the ones [`Workflow::standard`](crate::workflow::Workflow::standard)
+/// goes through; other workflows can add their own with [`Status::Custom`].
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
 pub enum Status {
     ToDo,
     InProgress,
+    Blocked,
+    InReview,
     Done,
+    WontFix,
+    Custom(StatusName),
+}
+
+/// The name of a [custom status](Status::Custom), such as `Triage`.
+///
+/// Names are 1 to [`MAX_LENGTH`](Self::MAX_LENGTH) ASCII letters or digits, starting with an
+/// uppercase letter, and can't be the name of a built-in status. They are stored inline so
+/// that [`Status`] can stay `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatusName(InlineStr<{ StatusName::MAX_LENGTH }>);
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum StatusNameError {
+    #[error("The status name cannot be empty")]
+    Empty,
+    #[error(
+        "The status name cannot be longer than {} characters",
+        StatusName::MAX_LENGTH
+    )]
+    TooLong,
+    #[error("The status name must start with an uppercase letter")]
+    MustStartWithLetter,
+    #[error("The status name can only contain letters and digits (found {0:?})")]
+    InvalidCharacter(char),
+    #[error("{0} is a built-in status")]
+    BuiltIn(Status),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [
+        Priority::Low,
+        Priority::Medium,
+        Priority::High,
+        Priority::Critical,
+    ];
+}
+
+impl Status {
+    /// Whether no more work is expected on the ticket.
+    ///
+    /// Custom statuses are open: a ticket is closed by moving it to `Done` or `WontFix`.
+    pub fn is_closed(self) -> bool {
+        matches!(self, Status::Done | Status::WontFix)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Status::ToDo => "ToDo",
+            Status::InProgress => "InProgress",
+            Status::Blocked => "Blocked",
+            Status::InReview => "InReview",
+            Status::Done => "Done",
+            Status::WontFix => "WontFix",
+            Status::Custom(name) => name.as_str(),
+        }
+    }
+
+    /// The built-in statuses.
+    pub const ALL: [Status; 6] = [
+        Status::ToDo,
+        Status::InProgress,
+        Status::Blocked,
+        Status::InReview,
+        Status::Done,
+        Status::WontFix,
+    ];
+}
+
+/// Parses the name of a built-in status, or else of a custom one.
+impl std::str::FromStr for Status {
+    type Err = StatusNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Status::ALL.into_iter().find(|status| status.as_str() == s) {
+            Some(status) => Ok(status),
+            None => s.parse().map(Status::Custom),
+        }
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl StatusName {
+    /// The maximum length of a name, in ASCII characters.
+    pub const MAX_LENGTH: usize = 16;
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl std::str::FromStr for StatusName {
+    type Err = StatusNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let first = s.chars().next().ok_or(StatusNameError::Empty)?;
+        if !first.is_ascii_uppercase() {
+            return Err(StatusNameError::MustStartWithLetter);
+        }
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(StatusNameError::InvalidCharacter(c));
+        }
+        let name = InlineStr::new(s).ok_or(StatusNameError::TooLong)?;
+        // Otherwise the same name would stand for two different statuses.
+        if let Some(status) = Status::ALL.into_iter().find(|status| status.as_str() == s) {
+            return Err(StatusNameError::BuiltIn(status));
+        }
+        Ok(Self(name))
+    }
+}
+
+impl std::fmt::Display for StatusName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Debug for StatusName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StatusName({})", self.as_str())
+    }
 }
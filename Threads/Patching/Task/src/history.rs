@@ -0,0 +1,85 @@
+//! The audit trail of the changes applied to tickets.
+use crate::data::{Priority, Status, Ticket};
+use crate::store::TicketId;
+use std::collections::BTreeSet;
+use std::time::SystemTime;
+use ticket_fields::{Label, TicketDescription, TicketTitle, UserId};
+
+/// A field of a ticket, with its value before and after a change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Title {
+        old: TicketTitle,
+        new: TicketTitle,
+    },
+    Description {
+        old: TicketDescription,
+        new: TicketDescription,
+    },
+    Status {
+        old: Status,
+        new: Status,
+    },
+    Assignee {
+        old: Option<UserId>,
+        new: Option<UserId>,
+    },
+    Watchers {
+        old: BTreeSet<UserId>,
+        new: BTreeSet<UserId>,
+    },
+    Labels {
+        old: BTreeSet<Label>,
+        new: BTreeSet<Label>,
+    },
+    Priority {
+        old: Priority,
+        new: Priority,
+    },
+    Due {
+        old: Option<SystemTime>,
+        new: Option<SystemTime>,
+    },
+    /// The ticket was deleted: this is the last change in its history.
+    Deleted,
+}
+
+/// The changes made to a ticket by a single patch, or its deletion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TicketEvent {
+    pub ticket: TicketId,
+    /// The version of the ticket after the event.
+    pub revision: u64,
+    /// Who applied the patch, if known.
+    pub author: Option<UserId>,
+    pub at: SystemTime,
+    pub changes: Vec<Change>,
+}
+
+/// Rebuild `ticket`, as it currently is, at an earlier `revision` by undoing `events`.
+///
+/// `events` must be the ticket's full history, oldest first.
+/// Returns `None` if the ticket never reached `revision`.
+pub(crate) fn rewind(mut ticket: Ticket, events: &[TicketEvent], revision: u64) -> Option<Ticket> {
+    if revision > ticket.version {
+        return None;
+    }
+    for event in events.iter().rev().take_while(|e| e.revision > revision) {
+        for change in event.changes.iter().rev() {
+            match change {
+                Change::Title { old, .. } => ticket.title = old.clone(),
+                Change::Description { old, .. } => ticket.description = old.clone(),
+                Change::Status { old, .. } => ticket.status = *old,
+                Change::Assignee { old, .. } => ticket.assignee = old.clone(),
+                Change::Watchers { old, .. } => ticket.watchers = old.clone(),
+                Change::Labels { old, .. } => ticket.labels = old.clone(),
+                Change::Priority { old, .. } => ticket.priority = *old,
+                Change::Due { old, .. } => ticket.due = *old,
+                // Only the history of a deleted ticket holds a deletion.
+                Change::Deleted => {}
+            }
+        }
+    }
+    ticket.version = revision;
+    Some(ticket)
+}
@@ -1,42 +1,251 @@
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
 
-// TODO: Implement the patching functionality.
-use crate::data::{Ticket, TicketDraft, TicketPatch};
+use crate::data::{
+    AssigneeFilter, Page, SortBy, Status, Ticket, TicketDraft, TicketFilter, TicketPage,
+    TicketPatch,
+};
+use crate::data::{ConflictError, NotFoundError};
+use crate::history::TicketEvent;
+use crate::sqlite::{InsertError, SqliteStore, StorageError, UpdateError};
 use crate::store::{TicketId, TicketStore};
+use crate::users::{UnknownUserError, User};
+use crate::workflow::TransitionError;
+use ticket_fields::UserId;
 
 pub mod data;
+pub mod history;
+pub mod sqlite;
 pub mod store;
+pub mod users;
+pub mod workflow;
 
 #[derive(Clone)]
 pub struct TicketStoreClient {
-    sender: SyncSender<Command>,
+    sender: SyncSender<Request>,
+    user: Option<UserId>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    restarts: Arc<AtomicUsize>,
 }
 
 impl TicketStoreClient {
-    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, OverloadedError> {
-        let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Insert {
-                draft,
-                response_channel: response_sender,
-            })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
+    /// A client acting on behalf of `user`: its updates are recorded in the history as made
+    /// by them, and [`assigned_to_me`](Self::assigned_to_me) lists their tickets.
+    pub fn acting_as(&self, user: UserId) -> Self {
+        Self {
+            user: Some(user),
+            ..self.clone()
+        }
+    }
+
+    /// A client that gives up on each request, with [`ClientError::Timeout`], if the server
+    /// hasn't answered within `timeout`.
+    ///
+    /// By default, clients wait for as long as it takes.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// A client whose requests must all be answered by `deadline`, on top of its
+    /// [timeout](Self::with_timeout), e.g. for a single call:
+    ///
+    /// ```
+    /// # use std::time::{Duration, Instant};
+    /// # let client = task_patching::launch(5);
+    /// let count = client
+    ///     .with_deadline(Instant::now() + Duration::from_secs(1))
+    ///     .count(None);
+    /// ```
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self.clone()
+        }
+    }
+
+    /// Add a user to the directory, or update their name if they are already in it.
+    pub fn add_user(&self, user: User) -> Result<(), ClientError> {
+        self.request(|response_channel| Command::AddUser {
+            user,
+            response_channel,
+        })
+    }
+
+    pub fn user(&self, id: UserId) -> Result<Option<User>, ClientError> {
+        self.request(|response_channel| Command::GetUser {
+            id,
+            response_channel,
+        })
+    }
+
+    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, ClientError> {
+        self.request(|response_channel| Command::Insert {
+            draft,
+            response_channel,
+        })
+    }
+
+    pub fn get(&self, id: TicketId) -> Result<Option<Ticket>, ClientError> {
+        self.request(|response_channel| Command::Get {
+            id,
+            response_channel,
+        })
+    }
+
+    /// Delete a ticket, returning it if there was one.
+    ///
+    /// Its history is kept, and ends with the deletion.
+    pub fn delete(&self, id: TicketId) -> Result<Option<Ticket>, ClientError> {
+        self.request(|response_channel| Command::Delete {
+            id,
+            author: self.user.clone(),
+            response_channel,
+        })
+    }
+
+    /// A page of the tickets in `status`, or of all tickets, by id.
+    pub fn list(&self, status: Option<Status>, page: Page) -> Result<TicketPage, ClientError> {
+        self.request(|response_channel| Command::List {
+            status,
+            page,
+            response_channel,
+        })
+    }
+
+    /// How many tickets are in `status`, or how many tickets there are.
+    pub fn count(&self, status: Option<Status>) -> Result<usize, ClientError> {
+        self.request(|response_channel| Command::Count {
+            status,
+            response_channel,
+        })
+    }
+
+    pub fn update(&self, ticket_patch: TicketPatch) -> Result<(), ClientError> {
+        self.request(|response_channel| Command::Update {
+            patch: ticket_patch,
+            author: self.user.clone(),
+            response_channel,
+        })
+    }
+
+    /// The changes applied to a ticket, oldest first.
+    pub fn history(&self, id: TicketId) -> Result<Vec<TicketEvent>, ClientError> {
+        self.request(|response_channel| Command::History {
+            id,
+            response_channel,
+        })
+    }
+
+    /// The ticket as it was at `revision`, if it exists and has reached that revision.
+    pub fn as_of(&self, id: TicketId, revision: u64) -> Result<Option<Ticket>, ClientError> {
+        self.request(|response_channel| Command::AsOf {
+            id,
+            revision,
+            response_channel,
+        })
+    }
+
+    /// The tickets matching `filter`, in the order given by `sort`.
+    pub fn find(&self, filter: TicketFilter, sort: SortBy) -> Result<Vec<Ticket>, ClientError> {
+        self.request(|response_channel| Command::Find {
+            filter,
+            sort,
+            response_channel,
+        })
     }
 
-    pub fn get(&self, id: TicketId) -> Result<Option<Ticket>, OverloadedError> {
+    /// The tickets assigned to the user this client is [acting as](Self::acting_as).
+    pub fn assigned_to_me(&self) -> Result<Vec<Ticket>, ClientError> {
+        let user = self
+            .user
+            .clone()
+            .ok_or(ClientError::Validation(ValidationError::Anonymous))?;
+        self.find(
+            TicketFilter {
+                assignee: Some(AssigneeFilter::User(user)),
+                ..TicketFilter::default()
+            },
+            SortBy::Priority,
+        )
+    }
+
+    pub fn unassigned(&self) -> Result<Vec<Ticket>, ClientError> {
+        self.find(
+            TicketFilter {
+                assignee: Some(AssigneeFilter::Unassigned),
+                ..TicketFilter::default()
+            },
+            SortBy::Priority,
+        )
+    }
+
+    /// The open tickets past their due date, most urgent first.
+    pub fn overdue(&self) -> Result<Vec<Ticket>, ClientError> {
+        self.find(
+            TicketFilter {
+                overdue_at: Some(SystemTime::now()),
+                ..TicketFilter::default()
+            },
+            SortBy::Priority,
+        )
+    }
+
+    /// How many times the server has been restarted after a crash, if it is
+    /// [supervised](supervise).
+    pub fn restarts(&self) -> usize {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    /// Send the command built by `command` and wait for the server's answer.
+    fn request<T, E>(
+        &self,
+        command: impl FnOnce(SyncSender<Result<T, E>>) -> Command,
+    ) -> Result<T, ClientError>
+    where
+        ClientError: From<E>,
+    {
+        // A timeout too long to be added to the current time is as good as none.
+        let timeout = self
+            .timeout
+            .and_then(|timeout| Instant::now().checked_add(timeout));
+        let deadline = timeout.into_iter().chain(self.deadline).min();
         let (response_sender, response_receiver) = sync_channel(1);
         self.sender
-            .try_send(Command::Get {
-                id,
-                response_channel: response_sender,
+            .try_send(Request {
+                command: command(response_sender),
+                deadline,
             })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
-    }
-
-    pub fn update(&self, ticket_patch: TicketPatch) -> Result<(), OverloadedError> {
-        /* TODO */
+            .map_err(|e| match e {
+                TrySendError::Full(_) => ClientError::Overloaded(OverloadedError),
+                TrySendError::Disconnected(_) => ClientError::ServerGone,
+            })?;
+        let response = match deadline {
+            Some(deadline) => {
+                response_receiver
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .map_err(|e| match e {
+                        RecvTimeoutError::Timeout => ClientError::Timeout,
+                        // The server drops the requests whose deadline has passed unanswered.
+                        RecvTimeoutError::Disconnected if Instant::now() >= deadline => {
+                            ClientError::Timeout
+                        }
+                        RecvTimeoutError::Disconnected => ClientError::ServerGone,
+                    })?
+            }
+            // If the server panics while handling the command, the response sender is
+            // dropped without an answer.
+            None => response_receiver
+                .recv()
+                .map_err(|_| ClientError::ServerGone)?,
+        };
+        Ok(response?)
     }
 }
 
@@ -44,55 +253,533 @@ impl TicketStoreClient {
 #[error("The store is overloaded")]
 pub struct OverloadedError;
 
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The server's queue is full: the request was not sent, and can be retried later.
+    #[error(transparent)]
+    Overloaded(#[from] OverloadedError),
+    /// The server has stopped, or crashed while handling the request.
+    ///
+    /// A [supervised](supervise) server is restarted right away: later requests go through.
+    #[error("The ticket server is gone")]
+    ServerGone,
+    #[error(transparent)]
+    NotFound(#[from] NotFoundError),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    #[error(transparent)]
+    Conflict(#[from] ConflictError),
+    /// No answer came before the client's timeout or the request's deadline.
+    ///
+    /// Only a request still queued when its deadline passes is sure to be dropped. One the
+    /// server had already picked up runs to completion, so a timed-out write may still have
+    /// been applied: read the ticket back, or set the patch's `expected_version`, before
+    /// retrying it.
+    #[error("The ticket server didn't answer in time")]
+    Timeout,
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+/// The request was understood, but refused because of what it asked for.
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
+    #[error(transparent)]
+    UnknownUser(#[from] UnknownUserError),
+    #[error("The client isn't acting as any user")]
+    Anonymous,
+}
+
+impl From<InsertError> for ClientError {
+    fn from(error: InsertError) -> Self {
+        match error {
+            InsertError::UnknownUser(e) => ValidationError::from(e).into(),
+            InsertError::Storage(e) => e.into(),
+        }
+    }
+}
+
+impl From<UpdateError> for ClientError {
+    fn from(error: UpdateError) -> Self {
+        match error {
+            UpdateError::NotFound(e) => e.into(),
+            UpdateError::Conflict(e) => e.into(),
+            UpdateError::Transition(e) => ValidationError::from(e).into(),
+            UpdateError::UnknownUser(e) => ValidationError::from(e).into(),
+            UpdateError::Storage(e) => e.into(),
+        }
+    }
+}
+
 pub fn launch(capacity: usize) -> TicketStoreClient {
+    serve(TicketStore::new(), capacity)
+}
+
+/// Launch a server whose tickets are kept in the SQLite database at `path`.
+///
+/// The database is opened, and migrated, before this returns.
+pub fn launch_sqlite(
+    path: impl AsRef<Path>,
+    capacity: usize,
+) -> Result<TicketStoreClient, StorageError> {
+    Ok(serve(SqliteStore::open(path)?, capacity))
+}
+
+/// Launch a server for a store that has already been set up, e.g. with a workflow:
+///
+/// ```
+/// # use task_patching::{serve, store::TicketStore, workflow::Workflow};
+/// let client = serve(TicketStore::new().with_workflow(Workflow::standard()), 5);
+/// ```
+///
+/// If the server crashes, it stays down: see [`supervise`] for a server that restarts.
+pub fn serve(store: impl Backend + Send + 'static, capacity: usize) -> TicketStoreClient {
+    let (sender, receiver) = sync_channel(capacity);
+    std::thread::spawn(move || server(receiver, store));
+    client(sender, Arc::default())
+}
+
+/// Launch a server that restarts whenever it crashes while handling a command.
+///
+/// A supervisor thread watches the server's thread: when it panics, a new one is started on
+/// the same queue, so clients carry on without noticing. The crashed command is lost, and its
+/// client gets [`ClientError::ServerGone`].
+///
+/// The new server gets its store back from the last snapshot, replaying the writes that went
+/// through since. Snapshots are taken less and less often as the store grows, so that copying
+/// the store stays a small part of the cost of each write.
+pub fn supervise(
+    store: impl Backend + Clone + Send + 'static,
+    capacity: usize,
+) -> TicketStoreClient {
     let (sender, receiver) = sync_channel(capacity);
-    std::thread::spawn(move || server(receiver));
-    TicketStoreClient { sender }
+    let restarts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&restarts);
+    std::thread::spawn(move || supervisor(receiver, store, &counter));
+    client(sender, restarts)
+}
+
+fn client(sender: SyncSender<Request>, restarts: Arc<AtomicUsize>) -> TicketStoreClient {
+    TicketStoreClient {
+        sender,
+        user: None,
+        timeout: None,
+        deadline: None,
+        restarts,
+    }
+}
+
+/// The operations the server dispatches to its store.
+pub trait Backend {
+    fn add_user(&mut self, user: User) -> Result<(), StorageError>;
+    fn user(&self, id: &UserId) -> Result<Option<User>, StorageError>;
+    fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, InsertError>;
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, StorageError>;
+    fn delete(
+        &mut self,
+        id: TicketId,
+        author: Option<UserId>,
+    ) -> Result<Option<Ticket>, StorageError>;
+    fn list(&self, status: Option<Status>, page: Page) -> Result<TicketPage, StorageError>;
+    fn count(&self, status: Option<Status>) -> Result<usize, StorageError>;
+    fn update(&mut self, patch: TicketPatch, author: Option<UserId>) -> Result<(), UpdateError>;
+    fn history(&self, id: TicketId) -> Result<Vec<TicketEvent>, StorageError>;
+    fn as_of(&self, id: TicketId, revision: u64) -> Result<Option<Ticket>, StorageError>;
+    fn find(&self, filter: &TicketFilter, sort: SortBy) -> Result<Vec<Ticket>, StorageError>;
+}
+
+impl Backend for TicketStore {
+    fn add_user(&mut self, user: User) -> Result<(), StorageError> {
+        self.add_user(user);
+        Ok(())
+    }
+
+    fn user(&self, id: &UserId) -> Result<Option<User>, StorageError> {
+        Ok(self.user(id).cloned())
+    }
+
+    fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, InsertError> {
+        Ok(self.add_ticket(draft)?)
+    }
+
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, StorageError> {
+        Ok(self.get(id).cloned())
+    }
+
+    fn delete(
+        &mut self,
+        id: TicketId,
+        author: Option<UserId>,
+    ) -> Result<Option<Ticket>, StorageError> {
+        Ok(self.delete(id, author))
+    }
+
+    fn list(&self, status: Option<Status>, page: Page) -> Result<TicketPage, StorageError> {
+        Ok(self.list(status, page))
+    }
+
+    fn count(&self, status: Option<Status>) -> Result<usize, StorageError> {
+        Ok(self.count(status))
+    }
+
+    fn update(&mut self, patch: TicketPatch, author: Option<UserId>) -> Result<(), UpdateError> {
+        Ok(self.update(patch, author)?)
+    }
+
+    fn history(&self, id: TicketId) -> Result<Vec<TicketEvent>, StorageError> {
+        Ok(self.history(id).to_vec())
+    }
+
+    fn as_of(&self, id: TicketId, revision: u64) -> Result<Option<Ticket>, StorageError> {
+        Ok(self.as_of(id, revision))
+    }
+
+    fn find(&self, filter: &TicketFilter, sort: SortBy) -> Result<Vec<Ticket>, StorageError> {
+        Ok(self.find(filter, sort).into_iter().cloned().collect())
+    }
+}
+
+impl Backend for SqliteStore {
+    fn add_user(&mut self, user: User) -> Result<(), StorageError> {
+        self.add_user(user)
+    }
+
+    fn user(&self, id: &UserId) -> Result<Option<User>, StorageError> {
+        self.user(id)
+    }
+
+    fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, InsertError> {
+        self.add_ticket(draft)
+    }
+
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, StorageError> {
+        self.get(id)
+    }
+
+    fn delete(
+        &mut self,
+        id: TicketId,
+        author: Option<UserId>,
+    ) -> Result<Option<Ticket>, StorageError> {
+        self.delete(id, author)
+    }
+
+    fn list(&self, status: Option<Status>, page: Page) -> Result<TicketPage, StorageError> {
+        self.list(status, page)
+    }
+
+    fn count(&self, status: Option<Status>) -> Result<usize, StorageError> {
+        self.count(status)
+    }
+
+    fn update(&mut self, patch: TicketPatch, author: Option<UserId>) -> Result<(), UpdateError> {
+        self.update(patch, author)
+    }
+
+    fn history(&self, id: TicketId) -> Result<Vec<TicketEvent>, StorageError> {
+        self.history(id)
+    }
+
+    fn as_of(&self, id: TicketId, revision: u64) -> Result<Option<Ticket>, StorageError> {
+        self.as_of(id, revision)
+    }
+
+    fn find(&self, filter: &TicketFilter, sort: SortBy) -> Result<Vec<Ticket>, StorageError> {
+        self.find(filter, sort)
+    }
+}
+
+/// A command, and the time by which its client needs the answer.
+///
+/// Requests are only built by [`TicketStoreClient`].
+pub struct Request {
+    command: Command,
+    deadline: Option<Instant>,
 }
 
 enum Command {
+    AddUser {
+        user: User,
+        response_channel: SyncSender<Result<(), StorageError>>,
+    },
+    GetUser {
+        id: UserId,
+        response_channel: SyncSender<Result<Option<User>, StorageError>>,
+    },
     Insert {
         draft: TicketDraft,
-        response_channel: SyncSender<TicketId>,
+        response_channel: SyncSender<Result<TicketId, InsertError>>,
     },
     Get {
         id: TicketId,
-        response_channel: SyncSender<Option<Ticket>>,
+        response_channel: SyncSender<Result<Option<Ticket>, StorageError>>,
+    },
+    Delete {
+        id: TicketId,
+        author: Option<UserId>,
+        response_channel: SyncSender<Result<Option<Ticket>, StorageError>>,
+    },
+    List {
+        status: Option<Status>,
+        page: Page,
+        response_channel: SyncSender<Result<TicketPage, StorageError>>,
+    },
+    Count {
+        status: Option<Status>,
+        response_channel: SyncSender<Result<usize, StorageError>>,
     },
     Update {
         patch: TicketPatch,
-        response_channel: SyncSender<()>,
+        author: Option<UserId>,
+        response_channel: SyncSender<Result<(), UpdateError>>,
+    },
+    History {
+        id: TicketId,
+        response_channel: SyncSender<Result<Vec<TicketEvent>, StorageError>>,
     },
+    AsOf {
+        id: TicketId,
+        revision: u64,
+        response_channel: SyncSender<Result<Option<Ticket>, StorageError>>,
+    },
+    Find {
+        filter: TicketFilter,
+        sort: SortBy,
+        response_channel: SyncSender<Result<Vec<Ticket>, StorageError>>,
+    },
+}
+
+/// Handle the requests sent on `receiver` until all their senders are dropped.
+pub fn server(receiver: Receiver<Request>, mut store: impl Backend) {
+    while let Some(command) = next_command(&receiver) {
+        handle(&mut store, command);
+    }
 }
 
-pub fn server(receiver: Receiver<Command>) {
-    let mut store = TicketStore::new();
+/// Restart the server on `receiver` each time its thread panics, until it shuts down cleanly.
+fn supervisor<B: Backend + Clone + Send + 'static>(
+    receiver: Receiver<Request>,
+    store: B,
+    restarts: &AtomicUsize,
+) {
+    let receiver = Arc::new(Mutex::new(receiver));
+    let recovery = Arc::new(Mutex::new(Recovery::new(store.clone())));
+    let mut store = store;
     loop {
-        match receiver.recv() {
-            Ok(Command::Insert {
-                draft,
-                response_channel,
-            }) => {
-                let id = store.add_ticket(draft);
-                let _ = response_channel.send(id);
-            }
-            Ok(Command::Get {
-                id,
-                response_channel,
-            }) => {
-                let ticket = store.get(id);
-                let _ = response_channel.send(ticket.cloned());
-            }
-            Ok(Command::Update {
-                patch,
-                response_channel,
-            }) => {
-                i/* TODO */            }
-            Err(_) => {
-                // There are no more senders, so we can safely break
-                // and shut down the server.
-                break;
+        let worker = std::thread::spawn({
+            let receiver = Arc::clone(&receiver);
+            let recovery = Arc::clone(&recovery);
+            move || supervised_server(&receiver, store, &recovery)
+        });
+        if worker.join().is_ok() {
+            return;
+        }
+        restarts.fetch_add(1, Ordering::Relaxed);
+        store = lock(&recovery).restore();
+    }
+}
+
+fn supervised_server<B: Backend + Clone>(
+    receiver: &Mutex<Receiver<Request>>,
+    mut store: B,
+    recovery: &Mutex<Recovery<B>>,
+) {
+    loop {
+        // The queue is only locked while waiting, so a crash while handling a command leaves
+        // it to the next server.
+        let Some(command) = next_command(&lock(receiver)) else {
+            return;
+        };
+        let write = Write::of(&command);
+        handle(&mut store, command);
+        if let Some(write) = write {
+            lock(recovery).record(write, &store);
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// What a supervisor needs to rebuild the store of a crashed server.
+struct Recovery<B> {
+    snapshot: B,
+    /// The writes that went through since the snapshot was taken.
+    log: Vec<Write>,
+    /// How many writes went through before the snapshot was taken.
+    writes: usize,
+}
+
+impl<B: Backend + Clone> Recovery<B> {
+    /// Writes to replay before a snapshot is always worth taking.
+    const MIN_LOG_LENGTH: usize = 64;
+
+    fn new(snapshot: B) -> Self {
+        Self {
+            snapshot,
+            log: Vec::new(),
+            writes: 0,
+        }
+    }
+
+    fn record(&mut self, write: Write, store: &B) {
+        self.log.push(write);
+        // Waiting for as many writes as the snapshot holds keeps copying the store at a
+        // constant cost per write, however large it grows.
+        if self.log.len() >= self.writes.max(Self::MIN_LOG_LENGTH) {
+            self.writes += self.log.len();
+            self.log.clear();
+            self.snapshot = store.clone();
+        }
+    }
+
+    fn restore(&self) -> B {
+        let mut store = self.snapshot.clone();
+        for write in &self.log {
+            handle(&mut store, write.command());
+        }
+        store
+    }
+}
+
+/// A command that changes the store, without the channel its answer was sent on.
+enum Write {
+    AddUser(User),
+    Insert(TicketDraft),
+    Delete(TicketId, Option<UserId>),
+    Update(TicketPatch, Option<UserId>),
+}
+
+impl Write {
+    fn of(command: &Command) -> Option<Self> {
+        match command {
+            Command::AddUser { user, .. } => Some(Write::AddUser(user.clone())),
+            Command::Insert { draft, .. } => Some(Write::Insert(draft.clone())),
+            Command::Delete { id, author, .. } => Some(Write::Delete(*id, author.clone())),
+            Command::Update { patch, author, .. } => {
+                Some(Write::Update(patch.clone(), author.clone()))
             }
+            _ => None,
+        }
+    }
+
+    /// The command to replay this write; its answer was already sent, and goes nowhere.
+    fn command(&self) -> Command {
+        match self {
+            Write::AddUser(user) => Command::AddUser {
+                user: user.clone(),
+                response_channel: sync_channel(1).0,
+            },
+            Write::Insert(draft) => Command::Insert {
+                draft: draft.clone(),
+                response_channel: sync_channel(1).0,
+            },
+            Write::Delete(id, author) => Command::Delete {
+                id: *id,
+                author: author.clone(),
+                response_channel: sync_channel(1).0,
+            },
+            Write::Update(patch, author) => Command::Update {
+                patch: patch.clone(),
+                author: author.clone(),
+                response_channel: sync_channel(1).0,
+            },
+        }
+    }
+}
+
+/// Wait for the next command whose client is still waiting for the answer.
+///
+/// Returns `None` once there are no more senders, so we can safely shut down the server.
+fn next_command(receiver: &Receiver<Request>) -> Option<Command> {
+    loop {
+        let request = receiver.recv().ok()?;
+        // Dropping an expired command, and its response channel, is all there is to do.
+        if request
+            .deadline
+            .is_none_or(|deadline| deadline > Instant::now())
+        {
+            return Some(request.command);
+        }
+    }
+}
+
+fn handle(store: &mut impl Backend, command: Command) {
+    match command {
+        Command::AddUser {
+            user,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.add_user(user));
+        }
+        Command::GetUser {
+            id,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.user(&id));
+        }
+        Command::Insert {
+            draft,
+            response_channel,
+        } => {
+            let id = store.add_ticket(draft);
+            let _ = response_channel.send(id);
+        }
+        Command::Get {
+            id,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.get(id));
+        }
+        Command::Delete {
+            id,
+            author,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.delete(id, author));
+        }
+        Command::List {
+            status,
+            page,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.list(status, page));
+        }
+        Command::Count {
+            status,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.count(status));
+        }
+        Command::Update {
+            patch,
+            author,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.update(patch, author));
+        }
+        Command::History {
+            id,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.history(id));
+        }
+        Command::AsOf {
+            id,
+            revision,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.as_of(id, revision));
+        }
+        Command::Find {
+            filter,
+            sort,
+            response_channel,
+        } => {
+            let _ = response_channel.send(store.find(&filter, sort));
         }
     }
 }
@@ -0,0 +1,932 @@
+//! A ticket store kept in an SQLite database.
+//!
+//! The schema is versioned through SQLite's `user_version`: opening a database applies the
+//! `MIGRATIONS` it hasn't seen yet, in order, each in its own transaction.
+//! Rows are validated when they are read back, so a row edited behind the store's back
+//! surfaces as a [`StorageError`] rather than as an invalid `Ticket`.
+use crate::data::{
+    AssigneeFilter, ConflictError, NotFoundError, Page, PatchError, Priority, SortBy, Status,
+    Ticket, TicketDraft, TicketFilter, TicketPage, TicketPatch,
+};
+use crate::history::{self, Change, TicketEvent};
+use crate::store::TicketId;
+use crate::users::{UnknownUserError, User};
+use crate::workflow::{TransitionError, Workflow};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ticket_fields::{
+    Label, LabelError, ProjectKey, ProjectKeyError, TicketDescriptionError, TicketTitleError,
+    UserId, UserIdError,
+};
+
+/// The schema migrations, in order: migration `i` brings the schema to version `i + 1`.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE tickets (
+        project TEXT NOT NULL,
+        number INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL,
+        PRIMARY KEY (project, number)
+    );
+    CREATE INDEX tickets_by_status ON tickets (status);",
+    // One row per changed field; the rows of an event share its revision.
+    "CREATE TABLE ticket_changes (
+        project TEXT NOT NULL,
+        number INTEGER NOT NULL,
+        revision INTEGER NOT NULL,
+        author TEXT,
+        at INTEGER NOT NULL,
+        field TEXT NOT NULL,
+        old TEXT NOT NULL,
+        new TEXT NOT NULL,
+        PRIMARY KEY (project, number, revision, field)
+    );",
+    "ALTER TABLE tickets ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+    UPDATE tickets SET version = (
+        SELECT COALESCE(MAX(revision), 0) FROM ticket_changes
+        WHERE ticket_changes.project = tickets.project
+          AND ticket_changes.number = tickets.number
+    );",
+    // `ticket_changes` can't hold NULLs: an assignee change to or from nobody uses ''.
+    "ALTER TABLE tickets ADD COLUMN assignee TEXT;",
+    // Watchers are stored in `ticket_changes` as a space-separated list.
+    "CREATE TABLE users (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL
+    );
+    CREATE TABLE ticket_watchers (
+        project TEXT NOT NULL,
+        number INTEGER NOT NULL,
+        user TEXT NOT NULL,
+        PRIMARY KEY (project, number, user)
+    );
+    ALTER TABLE tickets ADD COLUMN reporter TEXT;
+    CREATE INDEX tickets_by_assignee ON tickets (assignee);",
+    // Priorities are stored as their rank, from 0 for `Low`, so that they sort correctly.
+    // Due dates, like change times, are in milliseconds since the Unix epoch.
+    "ALTER TABLE tickets ADD COLUMN priority INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE tickets ADD COLUMN due INTEGER;
+    CREATE TABLE ticket_labels (
+        project TEXT NOT NULL,
+        number INTEGER NOT NULL,
+        label TEXT NOT NULL,
+        PRIMARY KEY (project, number, label)
+    );
+    CREATE INDEX tickets_by_priority ON tickets (priority, due);",
+    // Numbers are handed out from a counter, so that deleted tickets don't get reused.
+    "CREATE TABLE ticket_counters (
+        project TEXT PRIMARY KEY,
+        next INTEGER NOT NULL
+    );
+    INSERT INTO ticket_counters (project, next)
+    SELECT project, MAX(number) + 1 FROM tickets GROUP BY project;",
+    // Deleted tickets are moved here, as they were last, so that `as_of` can still rewind
+    // them. Their watchers and labels are left where they are.
+    "CREATE TABLE deleted_tickets AS SELECT * FROM tickets WHERE FALSE;
+    CREATE UNIQUE INDEX deleted_tickets_by_id ON deleted_tickets (project, number);",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("The database schema is at version {found}, but only up to {supported} is supported")]
+    UnsupportedSchema { found: usize, supported: usize },
+    #[error("Ticket {id} has an invalid title in the database")]
+    InvalidTitle {
+        id: TicketId,
+        #[source]
+        source: TicketTitleError,
+    },
+    #[error("Ticket {id} has an invalid description in the database")]
+    InvalidDescription {
+        id: TicketId,
+        #[source]
+        source: TicketDescriptionError,
+    },
+    #[error("Ticket {id} has an invalid status in the database ({status:?})")]
+    InvalidStatus { id: TicketId, status: String },
+    #[error("Ticket {id} has a change to an unknown field in the database ({field:?})")]
+    InvalidChange { id: TicketId, field: String },
+    #[error("Ticket {id} has an invalid {field} in the database ({value:?})")]
+    InvalidValue {
+        id: TicketId,
+        field: &'static str,
+        value: String,
+    },
+    #[error("Ticket {id} has an invalid label in the database")]
+    InvalidLabel {
+        id: TicketId,
+        #[source]
+        source: LabelError,
+    },
+    #[error("Ticket {id} refers to an invalid user id in the database")]
+    InvalidUser {
+        id: TicketId,
+        #[source]
+        source: UserIdError,
+    },
+    #[error("The database has a ticket in an invalid project ({project:?})")]
+    InvalidProject {
+        project: String,
+        #[source]
+        source: ProjectKeyError,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InsertError {
+    #[error(transparent)]
+    UnknownUser(#[from] UnknownUserError),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+impl From<rusqlite::Error> for InsertError {
+    fn from(error: rusqlite::Error) -> Self {
+        InsertError::Storage(error.into())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateError {
+    #[error(transparent)]
+    NotFound(#[from] NotFoundError),
+    #[error(transparent)]
+    Conflict(#[from] ConflictError),
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
+    #[error(transparent)]
+    UnknownUser(#[from] UnknownUserError),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+impl From<PatchError> for UpdateError {
+    fn from(error: PatchError) -> Self {
+        match error {
+            PatchError::NotFound(e) => UpdateError::NotFound(e),
+            PatchError::Conflict(e) => UpdateError::Conflict(e),
+            PatchError::Transition(e) => UpdateError::Transition(e),
+            PatchError::UnknownUser(e) => UpdateError::UnknownUser(e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for UpdateError {
+    fn from(error: rusqlite::Error) -> Self {
+        UpdateError::Storage(error.into())
+    }
+}
+
+pub struct SqliteStore {
+    connection: Connection,
+    workflow: Workflow,
+}
+
+impl SqliteStore {
+    /// Open the database at `path`, creating it if needed, and bring its schema up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::migrate(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::migrate(Connection::open_in_memory()?)
+    }
+
+    fn migrate(mut connection: Connection) -> Result<Self, StorageError> {
+        let version: usize =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(StorageError::UnsupportedSchema {
+                found: version,
+                supported: MIGRATIONS.len(),
+            });
+        }
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let transaction = connection.transaction()?;
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", i + 1)?;
+            transaction.commit()?;
+        }
+        Ok(Self {
+            connection,
+            workflow: Workflow::default(),
+        })
+    }
+
+    /// Enforce `workflow` on the status changes made through [`update`](Self::update).
+    ///
+    /// The workflow isn't stored in the database: it has to be set every time it is opened.
+    pub fn with_workflow(mut self, workflow: Workflow) -> Self {
+        self.workflow = workflow;
+        self
+    }
+
+    /// Add a user to the directory, or update their name if they are already in it.
+    pub fn add_user(&mut self, user: User) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT INTO users (id, name) VALUES (?1, ?2)
+             ON CONFLICT (id) DO UPDATE SET name = excluded.name",
+            params![user.id.as_str(), user.name],
+        )?;
+        Ok(())
+    }
+
+    pub fn user(&self, id: &UserId) -> Result<Option<User>, StorageError> {
+        let name = self
+            .connection
+            .query_row(
+                "SELECT name FROM users WHERE id = ?1",
+                [id.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(name.map(|name| User {
+            id: id.clone(),
+            name,
+        }))
+    }
+
+    /// Fails if the draft refers to a user that isn't in the directory.
+    pub fn add_ticket(&mut self, ticket: TicketDraft) -> Result<TicketId, InsertError> {
+        let transaction = self.connection.transaction()?;
+        if let Some(user) = unknown_user(&transaction, ticket.users())? {
+            return Err(UnknownUserError(user).into());
+        }
+        let project = ProjectKey::DEFAULT;
+        let number: u64 = transaction.query_row(
+            "INSERT INTO ticket_counters (project, next) VALUES (?1, 1)
+             ON CONFLICT (project) DO UPDATE SET next = next + 1
+             RETURNING next - 1",
+            [project.as_str()],
+            |row| row.get(0),
+        )?;
+        let id = TicketId::with_project(project, number);
+        transaction.execute(
+            "INSERT INTO tickets
+             (project, number, title, description, status, reporter, assignee, priority, due)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                project.as_str(),
+                number,
+                ticket.title.as_str(),
+                ticket.description.as_str(),
+                Status::ToDo.as_str(),
+                ticket.reporter.as_ref().map(UserId::as_str),
+                ticket.assignee.as_ref().map(UserId::as_str),
+                priority_to_sql(ticket.priority),
+                ticket.due.map(time_to_sql),
+            ],
+        )?;
+        insert_watchers(&transaction, id, &ticket.watchers)?;
+        insert_labels(&transaction, id, &ticket.labels)?;
+        transaction.commit()?;
+        Ok(id)
+    }
+
+    pub fn get(&self, id: TicketId) -> Result<Option<Ticket>, StorageError> {
+        fetch(&self.connection, "tickets", id)
+    }
+
+    /// Remove a ticket, returning it if there was one.
+    ///
+    /// The deletion is recorded in the ticket's history, which is kept.
+    /// The ids of deleted tickets are never reused.
+    pub fn delete(
+        &mut self,
+        id: TicketId,
+        author: Option<UserId>,
+    ) -> Result<Option<Ticket>, StorageError> {
+        let transaction = self.connection.transaction()?;
+        let Some(ticket) = fetch(&transaction, "tickets", id)? else {
+            return Ok(None);
+        };
+        transaction.execute(
+            "INSERT INTO deleted_tickets SELECT * FROM tickets WHERE project = ?1 AND number = ?2",
+            params![id.project().as_str(), id.number()],
+        )?;
+        transaction.execute(
+            "DELETE FROM tickets WHERE project = ?1 AND number = ?2",
+            params![id.project().as_str(), id.number()],
+        )?;
+        transaction.execute(
+            "INSERT INTO ticket_changes (project, number, revision, author, at, field, old, new)
+             VALUES (?1, ?2, ?3, ?4, ?5, 'deleted', '', '')",
+            params![
+                id.project().as_str(),
+                id.number(),
+                ticket.version + 1,
+                author.as_ref().map(UserId::as_str),
+                time_to_sql(SystemTime::now()),
+            ],
+        )?;
+        transaction.commit()?;
+        Ok(Some(ticket))
+    }
+
+    /// Apply `patch` to its ticket, and record what changed.
+    pub fn update(
+        &mut self,
+        patch: TicketPatch,
+        author: Option<UserId>,
+    ) -> Result<(), UpdateError> {
+        let id = patch.id;
+        let transaction = self.connection.transaction()?;
+        let Some(mut ticket) = fetch(&transaction, "tickets", id)? else {
+            return Err(NotFoundError(id).into());
+        };
+        patch.check_version(&ticket)?;
+        if let Some(user) = unknown_user(&transaction, patch.users())? {
+            return Err(UnknownUserError(user).into());
+        }
+        let status = ticket.status;
+        let changes = patch.apply(&mut ticket);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.workflow.check(status, &ticket)?;
+
+        let revision = ticket.version + 1;
+        transaction.execute(
+            "UPDATE tickets
+             SET title = ?3, description = ?4, status = ?5, assignee = ?6, priority = ?7,
+                 due = ?8, version = ?9
+             WHERE project = ?1 AND number = ?2",
+            params![
+                id.project().as_str(),
+                id.number(),
+                ticket.title.as_str(),
+                ticket.description.as_str(),
+                ticket.status.as_str(),
+                ticket.assignee.as_ref().map(UserId::as_str),
+                priority_to_sql(ticket.priority),
+                ticket.due.map(time_to_sql),
+                revision,
+            ],
+        )?;
+        let at = time_to_sql(SystemTime::now());
+        for change in &changes {
+            let (field, old, new) = match change {
+                Change::Title { old, new } => ("title", old.as_str().into(), new.as_str().into()),
+                Change::Description { old, new } => {
+                    ("description", old.as_str().into(), new.as_str().into())
+                }
+                Change::Status { old, new } => ("status", old.as_str().into(), new.as_str().into()),
+                Change::Assignee { old, new } => (
+                    "assignee",
+                    old.as_ref().map_or("", UserId::as_str).into(),
+                    new.as_ref().map_or("", UserId::as_str).into(),
+                ),
+                Change::Watchers { old, new } => {
+                    transaction.execute(
+                        "DELETE FROM ticket_watchers WHERE project = ?1 AND number = ?2",
+                        params![id.project().as_str(), id.number()],
+                    )?;
+                    insert_watchers(&transaction, id, new)?;
+                    ("watchers", join(old), join(new))
+                }
+                Change::Labels { old, new } => {
+                    transaction.execute(
+                        "DELETE FROM ticket_labels WHERE project = ?1 AND number = ?2",
+                        params![id.project().as_str(), id.number()],
+                    )?;
+                    insert_labels(&transaction, id, new)?;
+                    ("labels", join(old), join(new))
+                }
+                Change::Priority { old, new } => (
+                    "priority",
+                    priority_to_sql(*old).to_string(),
+                    priority_to_sql(*new).to_string(),
+                ),
+                Change::Due { old, new } => {
+                    let due = |due: &Option<SystemTime>| {
+                        due.map_or(String::new(), |due| time_to_sql(due).to_string())
+                    };
+                    ("due", due(old), due(new))
+                }
+                Change::Deleted => ("deleted", String::new(), String::new()),
+            };
+            let (old, new): (String, String) = (old, new);
+            transaction.execute(
+                "INSERT INTO ticket_changes (project, number, revision, author, at, field, old, new)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    id.project().as_str(),
+                    id.number(),
+                    revision,
+                    author.as_ref().map(UserId::as_str),
+                    at,
+                    field,
+                    old,
+                    new
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The changes applied to a ticket through [`update`](Self::update), and its deletion,
+    /// oldest first.
+    pub fn history(&self, id: TicketId) -> Result<Vec<TicketEvent>, StorageError> {
+        let mut statement = self.connection.prepare(
+            "SELECT revision, author, at, field, old, new FROM ticket_changes
+             WHERE project = ?1 AND number = ?2
+             ORDER BY revision, rowid",
+        )?;
+        let rows = statement.query_map(params![id.project().as_str(), id.number()], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        let mut events: Vec<TicketEvent> = Vec::new();
+        for row in rows {
+            let (revision, author, at, field, old, new) = row?;
+            let author = author.map(|author| user_from_sql(id, author)).transpose()?;
+            let change = change_from_sql(id, field, old, new)?;
+            match events.last_mut() {
+                Some(event) if event.revision == revision => event.changes.push(change),
+                _ => events.push(TicketEvent {
+                    ticket: id,
+                    revision,
+                    author,
+                    at: time_from_sql(at),
+                    changes: vec![change],
+                }),
+            }
+        }
+        Ok(events)
+    }
+
+    /// The ticket as it was at `revision`, if it has reached that revision, even if it has
+    /// since been deleted.
+    pub fn as_of(&self, id: TicketId, revision: u64) -> Result<Option<Ticket>, StorageError> {
+        let ticket = match self.get(id)? {
+            Some(ticket) => Some(ticket),
+            None => fetch(&self.connection, "deleted_tickets", id)?,
+        };
+        let Some(ticket) = ticket else {
+            return Ok(None);
+        };
+        Ok(history::rewind(ticket, &self.history(id)?, revision))
+    }
+
+    /// A page of the tickets in `status`, or of all tickets, by id.
+    pub fn list(&self, status: Option<Status>, page: Page) -> Result<TicketPage, StorageError> {
+        let mut conditions = vec!["TRUE".to_string()];
+        let mut values = Vec::new();
+        let mut bind = |value: Value| {
+            values.push(value);
+            format!("?{}", values.len())
+        };
+        if let Some(status) = status {
+            conditions.push(format!(
+                "status = {}",
+                bind(status.as_str().to_string().into())
+            ));
+        }
+        if let Some(after) = page.after {
+            conditions.push(format!(
+                "(project, number) > ({}, {})",
+                bind(after.project().as_str().to_string().into()),
+                bind((after.number() as i64).into()),
+            ));
+        }
+        // One more than the limit, to tell whether there is a next page.
+        let limit = bind((page.limit.get() as i64 + 1).into());
+        let tickets = self.tickets(
+            &format!(
+                "SELECT {TICKET_COLUMNS} FROM tickets WHERE {}
+                 ORDER BY project, number LIMIT {limit}",
+                conditions.join(" AND ")
+            ),
+            values,
+        )?;
+        Ok(TicketPage::collect(page, tickets))
+    }
+
+    /// How many tickets are in `status`, or how many tickets there are.
+    pub fn count(&self, status: Option<Status>) -> Result<usize, StorageError> {
+        let count = match status {
+            Some(status) => self.connection.query_row(
+                "SELECT COUNT(*) FROM tickets WHERE status = ?1",
+                [status.as_str()],
+                |row| row.get(0),
+            ),
+            None => self
+                .connection
+                .query_row("SELECT COUNT(*) FROM tickets", [], |row| row.get(0)),
+        }?;
+        Ok(count)
+    }
+
+    /// The tickets matching `filter`, in the order given by `sort`.
+    pub fn find(&self, filter: &TicketFilter, sort: SortBy) -> Result<Vec<Ticket>, StorageError> {
+        let mut conditions = vec!["TRUE".to_string()];
+        let mut values = Vec::new();
+        let mut bind = |value: Value| {
+            values.push(value);
+            format!("?{}", values.len())
+        };
+        match &filter.assignee {
+            None => {}
+            Some(AssigneeFilter::Unassigned) => conditions.push("assignee IS NULL".into()),
+            Some(AssigneeFilter::User(user)) => {
+                conditions.push(format!("assignee = {}", bind(user.to_string().into())));
+            }
+        }
+        if let Some(priority) = filter.priority {
+            conditions.push(format!(
+                "priority >= {}",
+                bind(priority_to_sql(priority).into())
+            ));
+        }
+        if let Some(label) = &filter.label {
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM ticket_labels AS l
+                 WHERE l.project = tickets.project AND l.number = tickets.number
+                   AND l.label = {})",
+                bind(label.to_string().into())
+            ));
+        }
+        if let Some(now) = filter.overdue_at {
+            conditions.push(format!(
+                "due < {} AND status NOT IN ({}, {})",
+                bind(time_to_sql(now).into()),
+                bind(Status::Done.as_str().to_string().into()),
+                bind(Status::WontFix.as_str().to_string().into()),
+            ));
+        }
+        let order = match sort {
+            SortBy::Id => "project, number",
+            SortBy::Priority => "priority DESC, due IS NULL, due, project, number",
+        };
+        self.tickets(
+            &format!(
+                "SELECT {TICKET_COLUMNS} FROM tickets WHERE {} ORDER BY {order}",
+                conditions.join(" AND ")
+            ),
+            values,
+        )
+    }
+
+    /// Fetch the tickets returned by `query`, which selects [`TICKET_COLUMNS`], in order.
+    fn tickets(&self, query: &str, values: Vec<Value>) -> Result<Vec<Ticket>, StorageError> {
+        fetch_all(&self.connection, query, values)
+    }
+}
+
+/// The first of `ids` that isn't in the `users` table, if any.
+fn unknown_user<'a>(
+    connection: &Connection,
+    ids: impl IntoIterator<Item = &'a UserId>,
+) -> Result<Option<UserId>, rusqlite::Error> {
+    let mut statement = connection.prepare_cached("SELECT 1 FROM users WHERE id = ?1")?;
+    for id in ids {
+        if !statement.exists([id.as_str()])? {
+            return Ok(Some(id.clone()));
+        }
+    }
+    Ok(None)
+}
+
+fn insert_watchers(
+    connection: &Connection,
+    id: TicketId,
+    watchers: &BTreeSet<UserId>,
+) -> Result<(), rusqlite::Error> {
+    let mut statement = connection.prepare_cached(
+        "INSERT INTO ticket_watchers (project, number, user) VALUES (?1, ?2, ?3)",
+    )?;
+    for watcher in watchers {
+        statement.execute(params![
+            id.project().as_str(),
+            id.number(),
+            watcher.as_str()
+        ])?;
+    }
+    Ok(())
+}
+
+fn insert_labels(
+    connection: &Connection,
+    id: TicketId,
+    labels: &BTreeSet<Label>,
+) -> Result<(), rusqlite::Error> {
+    let mut statement = connection
+        .prepare_cached("INSERT INTO ticket_labels (project, number, label) VALUES (?1, ?2, ?3)")?;
+    for label in labels {
+        statement.execute(params![id.project().as_str(), id.number(), label.as_str()])?;
+    }
+    Ok(())
+}
+
+/// The columns of `tickets` that [`fetch_all`] expects, in order.
+const TICKET_COLUMNS: &str =
+    "project, number, title, description, status, reporter, assignee, priority, due, version";
+
+/// How many tickets to fetch the watchers and labels of in one query.
+///
+/// Each ticket takes two parameters, well under SQLite's limit of 32766.
+const BATCH_SIZE: usize = 500;
+
+/// Fetch a ticket from `table`: `tickets`, or `deleted_tickets`.
+fn fetch(
+    connection: &Connection,
+    table: &str,
+    id: TicketId,
+) -> Result<Option<Ticket>, StorageError> {
+    let tickets = fetch_all(
+        connection,
+        &format!("SELECT {TICKET_COLUMNS} FROM {table} WHERE project = ?1 AND number = ?2"),
+        vec![
+            id.project().as_str().to_string().into(),
+            (id.number() as i64).into(),
+        ],
+    )?;
+    Ok(tickets.into_iter().next())
+}
+
+/// Fetch the tickets returned by `query`, in order, with their watchers and labels.
+///
+/// The watchers and labels are fetched for a whole batch of tickets at once, rather than
+/// with two more queries per ticket.
+fn fetch_all(
+    connection: &Connection,
+    query: &str,
+    values: Vec<Value>,
+) -> Result<Vec<Ticket>, StorageError> {
+    let mut statement = connection.prepare(query)?;
+    let rows = statement.query_map(params_from_iter(values), |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, u64>(1)?,
+            TicketRow {
+                title: row.get(2)?,
+                description: row.get(3)?,
+                status: row.get(4)?,
+                reporter: row.get(5)?,
+                assignee: row.get(6)?,
+                watchers: Vec::new(),
+                labels: Vec::new(),
+                priority: row.get(7)?,
+                due: row.get(8)?,
+                version: row.get(9)?,
+            },
+        ))
+    })?;
+    let mut rows: Vec<((String, u64), TicketRow)> = rows
+        .map(|row| row.map(|(project, number, row)| ((project, number), row)))
+        .collect::<Result<_, _>>()?;
+
+    for batch in rows.chunks_mut(BATCH_SIZE) {
+        let positions: HashMap<(String, u64), usize> = batch
+            .iter()
+            .enumerate()
+            .map(|(position, (key, _))| (key.clone(), position))
+            .collect();
+        let ids = (0..batch.len())
+            .map(|i| format!("(?{}, ?{})", 2 * i + 1, 2 * i + 2))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let keys: Vec<Value> = batch
+            .iter()
+            .flat_map(|((project, number), _)| [project.clone().into(), (*number as i64).into()])
+            .collect();
+        type Field = fn(&mut TicketRow) -> &mut Vec<String>;
+        let sets: [(&str, &str, Field); 2] = [
+            ("ticket_watchers", "user", |row| &mut row.watchers),
+            ("ticket_labels", "label", |row| &mut row.labels),
+        ];
+        for (table, column, set) in sets {
+            let mut statement = connection.prepare(&format!(
+                "SELECT project, number, {column} FROM {table}
+                 WHERE (project, number) IN (VALUES {ids})"
+            ))?;
+            let mut found = statement.query(params_from_iter(&keys))?;
+            while let Some(row) = found.next()? {
+                let key = (row.get::<_, String>(0)?, row.get::<_, u64>(1)?);
+                set(&mut batch[positions[&key]].1).push(row.get(2)?);
+            }
+        }
+    }
+
+    rows.into_iter()
+        .map(|((project, number), row)| {
+            let project = project
+                .parse()
+                .map_err(|source| StorageError::InvalidProject { project, source })?;
+            ticket_from_sql(TicketId::with_project(project, number), row)
+        })
+        .collect()
+}
+
+fn change_from_sql(
+    id: TicketId,
+    field: String,
+    old: String,
+    new: String,
+) -> Result<Change, StorageError> {
+    let title = |title: String| {
+        title
+            .try_into()
+            .map_err(|source| StorageError::InvalidTitle { id, source })
+    };
+    let description = |description: String| {
+        description
+            .try_into()
+            .map_err(|source| StorageError::InvalidDescription { id, source })
+    };
+    let status = |status: String| {
+        status
+            .parse()
+            .map_err(|_| StorageError::InvalidStatus { id, status })
+    };
+    match field.as_str() {
+        "deleted" => Ok(Change::Deleted),
+        "title" => Ok(Change::Title {
+            old: title(old)?,
+            new: title(new)?,
+        }),
+        "description" => Ok(Change::Description {
+            old: description(old)?,
+            new: description(new)?,
+        }),
+        "status" => Ok(Change::Status {
+            old: status(old)?,
+            new: status(new)?,
+        }),
+        "assignee" => {
+            let assignee = |assignee: String| {
+                Some(assignee)
+                    .filter(|assignee| !assignee.is_empty())
+                    .map(|assignee| user_from_sql(id, assignee))
+                    .transpose()
+            };
+            Ok(Change::Assignee {
+                old: assignee(old)?,
+                new: assignee(new)?,
+            })
+        }
+        "watchers" => {
+            let watchers = |watchers: String| {
+                watchers
+                    .split_whitespace()
+                    .map(|watcher| user_from_sql(id, watcher.into()))
+                    .collect::<Result<BTreeSet<_>, _>>()
+            };
+            Ok(Change::Watchers {
+                old: watchers(old)?,
+                new: watchers(new)?,
+            })
+        }
+        "labels" => {
+            let labels = |labels: String| {
+                labels
+                    .split_whitespace()
+                    .map(|label| label_from_sql(id, label.into()))
+                    .collect::<Result<BTreeSet<_>, _>>()
+            };
+            Ok(Change::Labels {
+                old: labels(old)?,
+                new: labels(new)?,
+            })
+        }
+        "priority" => {
+            let priority = |priority: String| {
+                priority.parse().ok().and_then(priority_from_sql).ok_or(
+                    StorageError::InvalidValue {
+                        id,
+                        field: "priority",
+                        value: priority,
+                    },
+                )
+            };
+            Ok(Change::Priority {
+                old: priority(old)?,
+                new: priority(new)?,
+            })
+        }
+        "due" => {
+            let due = |due: String| match due.as_str() {
+                "" => Ok(None),
+                millis => millis
+                    .parse()
+                    .map(|millis| Some(time_from_sql(millis)))
+                    .map_err(|_| StorageError::InvalidValue {
+                        id,
+                        field: "due date",
+                        value: due,
+                    }),
+            };
+            Ok(Change::Due {
+                old: due(old)?,
+                new: due(new)?,
+            })
+        }
+        _ => Err(StorageError::InvalidChange { id, field }),
+    }
+}
+
+/// A row of the `tickets` table, before validation.
+struct TicketRow {
+    title: String,
+    description: String,
+    status: String,
+    reporter: Option<String>,
+    assignee: Option<String>,
+    watchers: Vec<String>,
+    labels: Vec<String>,
+    priority: i64,
+    due: Option<i64>,
+    version: u64,
+}
+
+fn ticket_from_sql(id: TicketId, row: TicketRow) -> Result<Ticket, StorageError> {
+    let status = row.status;
+    Ok(Ticket {
+        id,
+        title: row
+            .title
+            .try_into()
+            .map_err(|source| StorageError::InvalidTitle { id, source })?,
+        description: row
+            .description
+            .try_into()
+            .map_err(|source| StorageError::InvalidDescription { id, source })?,
+        status: status
+            .parse()
+            .map_err(|_| StorageError::InvalidStatus { id, status })?,
+        reporter: row
+            .reporter
+            .map(|user| user_from_sql(id, user))
+            .transpose()?,
+        assignee: row
+            .assignee
+            .map(|user| user_from_sql(id, user))
+            .transpose()?,
+        watchers: row
+            .watchers
+            .into_iter()
+            .map(|user| user_from_sql(id, user))
+            .collect::<Result<_, _>>()?,
+        labels: row
+            .labels
+            .into_iter()
+            .map(|label| label_from_sql(id, label))
+            .collect::<Result<_, _>>()?,
+        priority: priority_from_sql(row.priority).ok_or_else(|| StorageError::InvalidValue {
+            id,
+            field: "priority",
+            value: row.priority.to_string(),
+        })?,
+        due: row.due.map(time_from_sql),
+        version: row.version,
+    })
+}
+
+fn user_from_sql(id: TicketId, user: String) -> Result<UserId, StorageError> {
+    user.try_into()
+        .map_err(|source| StorageError::InvalidUser { id, source })
+}
+
+fn label_from_sql(id: TicketId, label: String) -> Result<Label, StorageError> {
+    label
+        .try_into()
+        .map_err(|source| StorageError::InvalidLabel { id, source })
+}
+
+/// Join the values of a set, which never contain whitespace, for `ticket_changes`.
+fn join<T: ToString>(values: &BTreeSet<T>) -> String {
+    let values: Vec<String> = values.iter().map(T::to_string).collect();
+    values.join(" ")
+}
+
+fn time_to_sql(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn time_from_sql(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
+
+fn priority_to_sql(priority: Priority) -> i64 {
+    priority as i64
+}
+
+fn priority_from_sql(rank: i64) -> Option<Priority> {
+    Priority::ALL.get(usize::try_from(rank).ok()?).copied()
+}
@@ -1,12 +1,25 @@
-use crate::data::{Status, Ticket, TicketDraft};
+use crate::data::{
+    NotFoundError, Page, PatchError, SortBy, Status, Ticket, TicketDraft, TicketFilter, TicketPage,
+    TicketPatch,
+};
+use crate::history::{self, Change, TicketEvent};
+use crate::users::{UnknownUserError, User, UserRegistry};
+use crate::workflow::Workflow;
 use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::time::SystemTime;
+use ticket_fields::UserId;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct TicketId(u64);
+pub use ticket_fields::TicketId;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TicketStore {
     tickets: BTreeMap<TicketId, Ticket>,
+    /// Deleted tickets, as they were last, so that `as_of` can still rewind them.
+    deleted: BTreeMap<TicketId, Ticket>,
+    history: BTreeMap<TicketId, Vec<TicketEvent>>,
+    workflow: Workflow,
+    users: UserRegistry,
     counter: u64,
 }
 
@@ -14,28 +27,154 @@ impl TicketStore {
     pub fn new() -> Self {
         Self {
             tickets: BTreeMap::new(),
+            deleted: BTreeMap::new(),
+            history: BTreeMap::new(),
+            workflow: Workflow::default(),
+            users: UserRegistry::new(),
             counter: 0,
         }
     }
 
-    pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
-        let id = TicketId(self.counter);
+    /// Enforce `workflow` on the status changes made through [`update`](Self::update).
+    pub fn with_workflow(mut self, workflow: Workflow) -> Self {
+        self.workflow = workflow;
+        self
+    }
+
+    /// Add a user to the directory, or update their name if they are already in it.
+    pub fn add_user(&mut self, user: User) {
+        self.users.add(user);
+    }
+
+    pub fn user(&self, id: &UserId) -> Option<&User> {
+        self.users.get(id)
+    }
+
+    /// Fails if the draft refers to a user that isn't in the directory.
+    pub fn add_ticket(&mut self, ticket: TicketDraft) -> Result<TicketId, UnknownUserError> {
+        self.users.check(ticket.users())?;
+        let id = TicketId::new(self.counter);
         self.counter += 1;
         let ticket = Ticket {
             id,
             title: ticket.title,
             description: ticket.description,
             status: Status::ToDo,
+            reporter: ticket.reporter,
+            assignee: ticket.assignee,
+            watchers: ticket.watchers,
+            labels: ticket.labels,
+            priority: ticket.priority,
+            due: ticket.due,
+            version: 0,
         };
         self.tickets.insert(id, ticket);
-        id
+        Ok(id)
     }
 
     pub fn get(&self, id: TicketId) -> Option<&Ticket> {
         self.tickets.get(&id)
     }
 
+    /// Remove a ticket, returning it if there was one.
+    ///
+    /// The deletion is recorded in the ticket's history, which is kept.
+    /// The ids of deleted tickets are never reused.
+    pub fn delete(&mut self, id: TicketId, author: Option<UserId>) -> Option<Ticket> {
+        let ticket = self.tickets.remove(&id)?;
+        self.history.entry(id).or_default().push(TicketEvent {
+            ticket: id,
+            revision: ticket.version + 1,
+            author,
+            at: SystemTime::now(),
+            changes: vec![Change::Deleted],
+        });
+        self.deleted.insert(id, ticket.clone());
+        Some(ticket)
+    }
+
+    /// Changes made through the returned reference are not recorded in the history:
+    /// use [`update`](Self::update) for that.
     pub fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
         self.tickets.get_mut(&id)
     }
+
+    /// Apply `patch` to its ticket, and record what changed.
+    ///
+    /// The patch is rejected as a whole if it refers to an unknown user, or if it moves the
+    /// ticket through a transition the workflow doesn't allow.
+    pub fn update(&mut self, patch: TicketPatch, author: Option<UserId>) -> Result<(), PatchError> {
+        let Some(ticket) = self.tickets.get_mut(&patch.id) else {
+            return Err(NotFoundError(patch.id).into());
+        };
+        patch.check_version(ticket)?;
+        self.users.check(patch.users())?;
+        let mut patched = ticket.clone();
+        let changes = patch.apply(&mut patched);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.workflow.check(ticket.status, &patched)?;
+        *ticket = patched;
+        ticket.version += 1;
+        self.history
+            .entry(ticket.id)
+            .or_default()
+            .push(TicketEvent {
+                ticket: ticket.id,
+                revision: ticket.version,
+                author,
+                at: SystemTime::now(),
+                changes,
+            });
+        Ok(())
+    }
+
+    /// The changes applied to a ticket through [`update`](Self::update), and its deletion,
+    /// oldest first.
+    pub fn history(&self, id: TicketId) -> &[TicketEvent] {
+        self.history.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The ticket as it was at `revision`, if it has reached that revision, even if it has
+    /// since been deleted.
+    pub fn as_of(&self, id: TicketId, revision: u64) -> Option<Ticket> {
+        let ticket = self.get(id).or_else(|| self.deleted.get(&id))?;
+        history::rewind(ticket.clone(), self.history(id), revision)
+    }
+
+    /// A page of the tickets in `status`, or of all tickets, by id.
+    pub fn list(&self, status: Option<Status>, page: Page) -> TicketPage {
+        let start = page.after.map_or(Bound::Unbounded, Bound::Excluded);
+        let tickets = self
+            .tickets
+            .range((start, Bound::Unbounded))
+            .map(|(_, ticket)| ticket)
+            .filter(|ticket| status.is_none_or(|status| ticket.status == status))
+            .cloned();
+        TicketPage::collect(page, tickets)
+    }
+
+    /// How many tickets are in `status`, or how many tickets there are.
+    pub fn count(&self, status: Option<Status>) -> usize {
+        match status {
+            Some(status) => self
+                .tickets
+                .values()
+                .filter(|ticket| ticket.status == status)
+                .count(),
+            None => self.tickets.len(),
+        }
+    }
+
+    /// The tickets matching `filter`, in the order given by `sort`.
+    pub fn find(&self, filter: &TicketFilter, sort: SortBy) -> Vec<&Ticket> {
+        let mut tickets: Vec<_> = self
+            .tickets
+            .values()
+            .filter(|ticket| filter.matches(ticket))
+            .collect();
+        sort.sort(&mut tickets);
+        tickets
+    }
 }
@@ -0,0 +1,48 @@
+//! The people that tickets can refer to.
+use std::collections::BTreeMap;
+use ticket_fields::UserId;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct User {
+    pub id: UserId,
+    pub name: String,
+}
+
+/// A ticket refers to a user that isn't in the directory.
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[error("There is no user with id {0}")]
+pub struct UnknownUserError(pub UserId);
+
+/// The users known to an in-memory store.
+#[derive(Clone, Debug, Default)]
+pub struct UserRegistry {
+    users: BTreeMap<UserId, User>,
+}
+
+impl UserRegistry {
+    pub fn new() -> Self {
+        Self {
+            users: BTreeMap::new(),
+        }
+    }
+
+    /// Add a user, or update their name if they are already known.
+    pub fn add(&mut self, user: User) {
+        self.users.insert(user.id.clone(), user);
+    }
+
+    pub fn get(&self, id: &UserId) -> Option<&User> {
+        self.users.get(id)
+    }
+
+    /// Fail on the first of `ids` that isn't a known user.
+    pub fn check<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a UserId>,
+    ) -> Result<(), UnknownUserError> {
+        match ids.into_iter().find(|id| !self.users.contains_key(id)) {
+            Some(id) => Err(UnknownUserError(id.clone())),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Which status changes are allowed, and what a ticket needs to go through them.
+use crate::data::{Status, Ticket};
+use crate::store::TicketId;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A field that must be set on a ticket for it to go through a transition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequiredField {
+    Assignee,
+}
+
+impl RequiredField {
+    fn is_set(self, ticket: &Ticket) -> bool {
+        match self {
+            RequiredField::Assignee => ticket.assignee.is_some(),
+        }
+    }
+}
+
+impl fmt::Display for RequiredField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequiredField::Assignee => f.write_str("an assignee"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    #[error("Ticket {id} cannot move from {from} to {to}")]
+    NotAllowed {
+        id: TicketId,
+        from: Status,
+        to: Status,
+    },
+    #[error("Ticket {id} needs {field} to move from {from} to {to}")]
+    MissingField {
+        id: TicketId,
+        from: Status,
+        to: Status,
+        field: RequiredField,
+    },
+}
+
+/// A status change a [`Workflow`] allows, and the fields a ticket needs to go through it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub from: Status,
+    pub to: Status,
+    pub requires: Vec<RequiredField>,
+}
+
+/// The allowed status transitions, each with the fields it requires.
+///
+/// Workflows are plain data: collect them from [`Transition`]s, which may go through
+/// [custom statuses](Status::Custom), or build them with [`allow`](Self::allow) and
+/// [`allow_requiring`](Self::allow_requiring).
+///
+/// The default workflow allows every transition and requires nothing.
+/// [`Workflow::standard`] is a stricter starting point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Workflow {
+    transitions: HashMap<(Status, Status), Vec<RequiredField>>,
+    // Whether the transitions missing from the map are allowed, with no required field.
+    allow_others: bool,
+}
+
+impl Workflow {
+    /// A workflow without any transition: statuses can't change until some are allowed.
+    pub fn new() -> Self {
+        Self {
+            transitions: HashMap::new(),
+            allow_others: false,
+        }
+    }
+
+    /// ```text
+    /// ToDo ──▶ InProgress ──▶ InReview ──▶ Done
+    ///  ▲  ╲        │  ▲           │          │
+    ///  │   ╲       ▼  │           ▼          │
+    ///  │    ╲──▶ Blocked      InProgress     │
+    ///  │                                     │
+    ///  └──────── reopened from Done, WontFix ┘
+    /// ```
+    ///
+    /// Starting work requires an assignee. Any open ticket can be closed as `WontFix`.
+    pub fn standard() -> Self {
+        use Status::*;
+
+        const ASSIGNED: &[RequiredField] = &[RequiredField::Assignee];
+        let transitions = [
+            (ToDo, InProgress, ASSIGNED),
+            (Blocked, InProgress, ASSIGNED),
+            (ToDo, Blocked, &[]),
+            (InProgress, Blocked, &[]),
+            (InProgress, ToDo, &[]),
+            (InProgress, InReview, &[]),
+            (InReview, InProgress, &[]),
+            (InReview, Done, &[]),
+            (Blocked, ToDo, &[]),
+            (Done, ToDo, &[]),
+            (WontFix, ToDo, &[]),
+        ];
+        let close = [ToDo, InProgress, Blocked, InReview].map(|from| (from, WontFix, &[][..]));
+        transitions
+            .into_iter()
+            .chain(close)
+            .map(|(from, to, requires)| Transition {
+                from,
+                to,
+                requires: requires.to_vec(),
+            })
+            .collect()
+    }
+
+    pub fn allow(self, from: Status, to: Status) -> Self {
+        self.allow_requiring(from, to, &[])
+    }
+
+    pub fn allow_requiring(mut self, from: Status, to: Status, fields: &[RequiredField]) -> Self {
+        self.transitions.insert((from, to), fields.to_vec());
+        self
+    }
+
+    /// Check that `ticket`, as it would be after a patch, may have moved out of `from`.
+    pub fn check(&self, from: Status, ticket: &Ticket) -> Result<(), TransitionError> {
+        let to = ticket.status;
+        if from == to {
+            return Ok(());
+        }
+        let id = ticket.id;
+        let required = match self.transitions.get(&(from, to)) {
+            Some(required) => required.as_slice(),
+            None if self.allow_others => &[],
+            None => return Err(TransitionError::NotAllowed { id, from, to }),
+        };
+        match required.iter().find(|field| !field.is_set(ticket)) {
+            Some(&field) => Err(TransitionError::MissingField {
+                id,
+                from,
+                to,
+                field,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        Self {
+            transitions: HashMap::new(),
+            allow_others: true,
+        }
+    }
+}
+
+impl FromIterator<Transition> for Workflow {
+    /// A workflow that only allows `transitions`.
+    fn from_iter<T: IntoIterator<Item = Transition>>(transitions: T) -> Self {
+        transitions
+            .into_iter()
+            .fold(Self::new(), |workflow, transition| {
+                workflow.allow_requiring(transition.from, transition.to, &transition.requires)
+            })
+    }
+}
@@ -0,0 +1,1198 @@
+use common::fixtures::{self, FixtureStatus, FixtureTicket, SizeProfile};
+use std::collections::BTreeSet;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant, SystemTime};
+use task_patching::data::{
+    ConflictError, NotFoundError, Page, Priority, SortBy, Status, StatusNameError, Ticket,
+    TicketDraft, TicketFilter, TicketPage, TicketPatch,
+};
+use task_patching::history::{Change, TicketEvent};
+use task_patching::sqlite::{InsertError, StorageError, UpdateError};
+use task_patching::store::{TicketId, TicketStore};
+use task_patching::users::{UnknownUserError, User};
+use task_patching::workflow::{RequiredField, Transition, TransitionError, Workflow};
+use task_patching::{
+    launch, serve, supervise, Backend, ClientError, TicketStoreClient, ValidationError,
+};
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+use ticket_fields::{Label, TicketTitle, UserId};
+
+fn user(id: &str) -> UserId {
+    id.parse().unwrap()
+}
+
+#[test]
+fn works() {
+    let client = launch(5);
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+        reporter: None,
+        assignee: None,
+        watchers: BTreeSet::new(),
+        labels: BTreeSet::new(),
+        priority: Priority::default(),
+        due: None,
+    };
+    let ticket_id = client.insert(draft.clone()).unwrap();
+
+    let ticket = client.get(ticket_id).unwrap().unwrap();
+    assert_eq!(ticket_id, ticket.id);
+    assert_eq!(ticket.status, Status::ToDo);
+    assert_eq!(ticket.title, draft.title);
+    assert_eq!(ticket.description, draft.description);
+
+    let patch = TicketPatch {
+        id: ticket_id,
+        title: Some(TicketTitle::try_from("New title").unwrap()),
+        description: None,
+        status: Some(Status::InProgress),
+        assignee: None,
+        watchers: None,
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: None,
+    };
+    client.update(patch).unwrap();
+
+    let ticket = client.get(ticket_id).unwrap().unwrap();
+    assert_eq!(ticket.title, TicketTitle::try_from("New title").unwrap());
+    assert_eq!(ticket.description, draft.description);
+    assert_eq!(ticket.status, Status::InProgress);
+}
+
+/// Two authors patch the same ticket: check the recorded history and past revisions.
+fn check_history(client: &TicketStoreClient) {
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+        reporter: None,
+        assignee: None,
+        watchers: BTreeSet::new(),
+        labels: BTreeSet::new(),
+        priority: Priority::default(),
+        due: None,
+    };
+    let id = client.insert(draft.clone()).unwrap();
+    let patch = |title: Option<&str>, status| TicketPatch {
+        id,
+        title: title.map(|title| title.try_into().unwrap()),
+        description: None,
+        status,
+        assignee: None,
+        watchers: None,
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: None,
+    };
+
+    let alice = client.acting_as(user("alice"));
+    alice
+        .update(patch(Some("Renamed"), Some(Status::InProgress)))
+        .unwrap();
+    // Patches that change nothing are not recorded.
+    alice.update(patch(Some("Renamed"), None)).unwrap();
+    client
+        .acting_as(user("bob"))
+        .update(patch(None, Some(Status::Done)))
+        .unwrap();
+
+    let history = client.history(id).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].revision, 1);
+    assert_eq!(history[0].author, Some(user("alice")));
+    assert_eq!(
+        history[0].changes,
+        vec![
+            Change::Title {
+                old: draft.title.clone(),
+                new: "Renamed".try_into().unwrap()
+            },
+            Change::Status {
+                old: Status::ToDo,
+                new: Status::InProgress
+            },
+        ]
+    );
+    assert_eq!(history[1].revision, 2);
+    assert_eq!(history[1].author, Some(user("bob")));
+    assert!(history[0].at <= history[1].at);
+
+    let original = client.as_of(id, 0).unwrap().unwrap();
+    assert_eq!(original.title, draft.title);
+    assert_eq!(original.status, Status::ToDo);
+    let first = client.as_of(id, 1).unwrap().unwrap();
+    assert_eq!(first.title.as_str(), "Renamed");
+    assert_eq!(first.status, Status::InProgress);
+    assert_eq!(client.as_of(id, 2).unwrap(), client.get(id).unwrap());
+    assert_eq!(client.as_of(id, 3).unwrap(), None);
+
+    // A deleted ticket can still be rebuilt at the revisions it went through.
+    let last = client.delete(id).unwrap();
+    assert_eq!(client.as_of(id, 2).unwrap(), last);
+    assert_eq!(client.as_of(id, 1).unwrap().unwrap(), first);
+    assert_eq!(client.as_of(id, 0).unwrap().unwrap(), original);
+    assert_eq!(client.as_of(id, 3).unwrap(), None);
+}
+
+#[test]
+fn records_history() {
+    check_history(&launch(5));
+}
+
+#[test]
+fn missing_tickets_are_reported() {
+    let client = launch(5);
+    let id = client.insert(draft()).unwrap();
+    client.delete(id).unwrap();
+    let patch = TicketPatch {
+        id,
+        title: None,
+        description: None,
+        status: Some(Status::Done),
+        assignee: None,
+        watchers: None,
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: None,
+    };
+    match client.update(patch) {
+        Err(ClientError::NotFound(e)) => assert_eq!(e, NotFoundError(id)),
+        other => panic!("Expected a missing ticket, got {other:?}"),
+    }
+}
+
+/// A store that panics on every request.
+struct Crashing;
+
+impl Backend for Crashing {
+    fn add_user(&mut self, _: User) -> Result<(), StorageError> {
+        panic!("The store crashed")
+    }
+    fn user(&self, _: &UserId) -> Result<Option<User>, StorageError> {
+        panic!("The store crashed")
+    }
+    fn add_ticket(&mut self, _: TicketDraft) -> Result<TicketId, InsertError> {
+        panic!("The store crashed")
+    }
+    fn get(&self, _: TicketId) -> Result<Option<Ticket>, StorageError> {
+        panic!("The store crashed")
+    }
+    fn delete(&mut self, _: TicketId, _: Option<UserId>) -> Result<Option<Ticket>, StorageError> {
+        panic!("The store crashed")
+    }
+    fn list(&self, _: Option<Status>, _: Page) -> Result<TicketPage, StorageError> {
+        panic!("The store crashed")
+    }
+    fn count(&self, _: Option<Status>) -> Result<usize, StorageError> {
+        panic!("The store crashed")
+    }
+    fn update(&mut self, _: TicketPatch, _: Option<UserId>) -> Result<(), UpdateError> {
+        panic!("The store crashed")
+    }
+    fn history(&self, _: TicketId) -> Result<Vec<TicketEvent>, StorageError> {
+        panic!("The store crashed")
+    }
+    fn as_of(&self, _: TicketId, _: u64) -> Result<Option<Ticket>, StorageError> {
+        panic!("The store crashed")
+    }
+    fn find(&self, _: &TicketFilter, _: SortBy) -> Result<Vec<Ticket>, StorageError> {
+        panic!("The store crashed")
+    }
+}
+
+#[test]
+fn a_crashed_server_is_an_error() {
+    let client = serve(Crashing, 5);
+    assert!(matches!(client.count(None), Err(ClientError::ServerGone)));
+    // The server thread is gone for good.
+    assert!(matches!(
+        client.insert(draft()),
+        Err(ClientError::ServerGone)
+    ));
+}
+
+/// A store that takes its time to count tickets, crashes halfway through deletions, and
+/// crashes when asked for a ticket's history.
+#[derive(Clone)]
+struct Faulty(TicketStore, Duration);
+
+impl Backend for Faulty {
+    fn add_user(&mut self, user: User) -> Result<(), StorageError> {
+        self.0.add_user(user);
+        Ok(())
+    }
+    fn user(&self, id: &UserId) -> Result<Option<User>, StorageError> {
+        Ok(self.0.user(id).cloned())
+    }
+    fn add_ticket(&mut self, draft: TicketDraft) -> Result<TicketId, InsertError> {
+        Ok(self.0.add_ticket(draft)?)
+    }
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, StorageError> {
+        Ok(self.0.get(id).cloned())
+    }
+    fn delete(
+        &mut self,
+        id: TicketId,
+        author: Option<UserId>,
+    ) -> Result<Option<Ticket>, StorageError> {
+        self.0.delete(id, author);
+        panic!("The store crashed")
+    }
+    fn list(&self, status: Option<Status>, page: Page) -> Result<TicketPage, StorageError> {
+        Ok(self.0.list(status, page))
+    }
+    fn count(&self, status: Option<Status>) -> Result<usize, StorageError> {
+        std::thread::sleep(self.1);
+        Ok(self.0.count(status))
+    }
+    fn update(&mut self, patch: TicketPatch, author: Option<UserId>) -> Result<(), UpdateError> {
+        Ok(self.0.update(patch, author)?)
+    }
+    fn history(&self, _: TicketId) -> Result<Vec<TicketEvent>, StorageError> {
+        panic!("The store crashed")
+    }
+    fn as_of(&self, id: TicketId, revision: u64) -> Result<Option<Ticket>, StorageError> {
+        Ok(self.0.as_of(id, revision))
+    }
+    fn find(&self, filter: &TicketFilter, sort: SortBy) -> Result<Vec<Ticket>, StorageError> {
+        Ok(self.0.find(filter, sort).into_iter().cloned().collect())
+    }
+}
+
+#[test]
+fn slow_requests_time_out() {
+    let client = serve(Faulty(TicketStore::new(), Duration::from_millis(300)), 5);
+    let impatient = client.with_timeout(Duration::from_millis(50));
+
+    let start = Instant::now();
+    assert!(matches!(impatient.count(None), Err(ClientError::Timeout)));
+    assert!(start.elapsed() < Duration::from_millis(300));
+
+    // The server is still counting: this insert expires while it waits in the queue, and is
+    // dropped without being applied.
+    let deadline = Instant::now() + Duration::from_millis(50);
+    assert!(matches!(
+        client.with_deadline(deadline).insert(draft()),
+        Err(ClientError::Timeout)
+    ));
+    assert_eq!(client.count(None).unwrap(), 0);
+
+    // Requests answered in time go through as usual.
+    let id = impatient.insert(draft()).unwrap();
+    assert!(impatient.get(id).unwrap().is_some());
+}
+
+#[test]
+fn endless_timeouts_never_expire() {
+    let client = launch(5).with_timeout(Duration::MAX);
+    let id = client.insert(draft()).unwrap();
+    assert!(client.get(id).unwrap().is_some());
+
+    let deadline = Instant::now() + Duration::from_secs(60);
+    assert_eq!(client.with_deadline(deadline).count(None).unwrap(), 1);
+}
+
+#[test]
+fn a_supervised_server_restarts() {
+    let client = supervise(Faulty(TicketStore::new(), Duration::ZERO), 5);
+    let id = client.insert(draft()).unwrap();
+    assert!(matches!(client.delete(id), Err(ClientError::ServerGone)));
+
+    // The half-done deletion was thrown away with the crashed server, and the clients carry on
+    // with its replacement as usual.
+    let other = client.clone();
+    assert!(other.get(id).unwrap().is_some());
+    other.insert(draft()).unwrap();
+    assert_eq!(client.count(None).unwrap(), 2);
+    assert_eq!(client.restarts(), 1);
+}
+
+#[test]
+fn a_crashed_read_keeps_earlier_writes() {
+    let client = supervise(Faulty(TicketStore::new(), Duration::ZERO), 5);
+    let first = client.insert(draft()).unwrap();
+    assert!(matches!(
+        client.history(first),
+        Err(ClientError::ServerGone)
+    ));
+
+    assert!(client.get(first).unwrap().is_some());
+    assert_ne!(client.insert(draft()).unwrap(), first);
+    assert_eq!(client.restarts(), 1);
+}
+
+#[test]
+fn a_restarted_server_replays_the_writes_since_its_last_snapshot() {
+    let client = supervise(Faulty(TicketStore::new(), Duration::ZERO), 5);
+    let ids: Vec<_> = (0..100).map(|_| client.insert(draft()).unwrap()).collect();
+    assert!(matches!(
+        client.delete(ids[0]),
+        Err(ClientError::ServerGone)
+    ));
+
+    assert_eq!(client.count(None).unwrap(), 100);
+    assert!(matches!(
+        client.delete(ids[99]),
+        Err(ClientError::ServerGone)
+    ));
+    assert_eq!(client.count(None).unwrap(), 100);
+    assert_eq!(client.restarts(), 2);
+}
+
+/// Two clients patch the same version of a ticket: the second one must be rejected.
+fn check_conflicts(client: &TicketStoreClient) {
+    let id = client
+        .insert(TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+            reporter: None,
+            assignee: None,
+            watchers: BTreeSet::new(),
+            labels: BTreeSet::new(),
+            priority: Priority::default(),
+            due: None,
+        })
+        .unwrap();
+    let version = client.get(id).unwrap().unwrap().version;
+    assert_eq!(version, 0);
+    let patch = |status| TicketPatch {
+        id,
+        title: None,
+        description: None,
+        status: Some(status),
+        assignee: None,
+        watchers: None,
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: Some(version),
+    };
+
+    client.update(patch(Status::InProgress)).unwrap();
+    let ticket = client.get(id).unwrap().unwrap();
+    assert_eq!(ticket.version, 1);
+
+    match client.update(patch(Status::Done)) {
+        Err(ClientError::Conflict(conflict)) => assert_eq!(
+            conflict,
+            ConflictError {
+                id,
+                expected: 0,
+                current: 1
+            }
+        ),
+        other => panic!("Expected a conflict, got {other:?}"),
+    }
+    assert_eq!(client.get(id).unwrap().unwrap(), ticket);
+
+    // Patches without an expected version still go through.
+    client
+        .update(TicketPatch {
+            expected_version: None,
+            ..patch(Status::Done)
+        })
+        .unwrap();
+    assert_eq!(client.get(id).unwrap().unwrap().version, 2);
+}
+
+#[test]
+fn rejects_stale_patches() {
+    check_conflicts(&launch(5));
+}
+
+/// Walk a ticket through the standard workflow, trying a few shortcuts on the way.
+fn check_workflow(client: &TicketStoreClient) {
+    client
+        .add_user(User {
+            id: user("alice"),
+            name: "Alice".into(),
+        })
+        .unwrap();
+    let id = client
+        .insert(TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+            reporter: None,
+            assignee: None,
+            watchers: BTreeSet::new(),
+            labels: BTreeSet::new(),
+            priority: Priority::default(),
+            due: None,
+        })
+        .unwrap();
+    let patch = |status, assignee: Option<&str>| TicketPatch {
+        id,
+        title: None,
+        description: None,
+        status: Some(status),
+        assignee: assignee.map(|assignee| Some(user(assignee))),
+        watchers: None,
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: None,
+    };
+
+    match client.update(patch(Status::Done, None)) {
+        Err(ClientError::Validation(ValidationError::Transition(e))) => assert_eq!(
+            e,
+            TransitionError::NotAllowed {
+                id,
+                from: Status::ToDo,
+                to: Status::Done
+            }
+        ),
+        other => panic!("Expected a rejected transition, got {other:?}"),
+    }
+    match client.update(patch(Status::InProgress, None)) {
+        Err(ClientError::Validation(ValidationError::Transition(e))) => assert_eq!(
+            e,
+            TransitionError::MissingField {
+                id,
+                from: Status::ToDo,
+                to: Status::InProgress,
+                field: RequiredField::Assignee
+            }
+        ),
+        other => panic!("Expected a missing assignee, got {other:?}"),
+    }
+    let ticket = client.get(id).unwrap().unwrap();
+    assert_eq!(ticket.status, Status::ToDo);
+    assert_eq!(ticket.version, 0);
+
+    // The assignee can be set by the same patch that starts the work.
+    client
+        .update(patch(Status::InProgress, Some("alice")))
+        .unwrap();
+    client.update(patch(Status::InReview, None)).unwrap();
+    client.update(patch(Status::Done, None)).unwrap();
+    let ticket = client.get(id).unwrap().unwrap();
+    assert_eq!(ticket.status, Status::Done);
+    assert_eq!(ticket.assignee, Some(user("alice")));
+
+    let history = client.history(id).unwrap();
+    assert_eq!(
+        history[0].changes[1],
+        Change::Assignee {
+            old: None,
+            new: Some(user("alice"))
+        }
+    );
+    assert_eq!(client.as_of(id, 0).unwrap().unwrap().assignee, None);
+}
+
+#[test]
+fn enforces_the_workflow() {
+    check_workflow(&serve(
+        TicketStore::new().with_workflow(Workflow::standard()),
+        5,
+    ));
+}
+
+/// New tickets go through triage, and need an assignee to leave it.
+fn triage_workflow() -> Workflow {
+    let triage = Status::Custom("Triage".parse().unwrap());
+    [
+        Transition {
+            from: Status::ToDo,
+            to: triage,
+            requires: vec![],
+        },
+        Transition {
+            from: triage,
+            to: Status::Done,
+            requires: vec![RequiredField::Assignee],
+        },
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn check_custom_workflow(client: &TicketStoreClient) {
+    client
+        .add_user(User {
+            id: user("alice"),
+            name: "Alice".into(),
+        })
+        .unwrap();
+    let id = client.insert(draft()).unwrap();
+    let triage = Status::Custom("Triage".parse().unwrap());
+    let patch = |status, assignee: Option<&str>| TicketPatch {
+        id,
+        title: None,
+        description: None,
+        status: Some(status),
+        assignee: assignee.map(|assignee| Some(user(assignee))),
+        watchers: None,
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: None,
+    };
+
+    assert!(matches!(
+        client.update(patch(Status::Done, None)),
+        Err(ClientError::Validation(ValidationError::Transition(
+            TransitionError::NotAllowed { .. }
+        )))
+    ));
+    client.update(patch(triage, None)).unwrap();
+    assert_eq!(client.get(id).unwrap().unwrap().status, triage);
+    let Err(ClientError::Validation(ValidationError::Transition(
+        error @ TransitionError::MissingField {
+            field: RequiredField::Assignee,
+            ..
+        },
+    ))) = client.update(patch(Status::Done, None))
+    else {
+        panic!("moving to Done requires an assignee");
+    };
+    assert_eq!(
+        error.to_string(),
+        format!("Ticket {id} needs an assignee to move from Triage to Done")
+    );
+    client.update(patch(Status::Done, Some("alice"))).unwrap();
+
+    assert_eq!(
+        client.history(id).unwrap()[0].changes,
+        vec![Change::Status {
+            old: Status::ToDo,
+            new: triage
+        }]
+    );
+}
+
+#[test]
+fn follows_a_custom_workflow() {
+    check_custom_workflow(&serve(
+        TicketStore::new().with_workflow(triage_workflow()),
+        5,
+    ));
+}
+
+#[test]
+fn custom_status_names() {
+    assert_eq!("Done".parse::<Status>(), Ok(Status::Done));
+    let triage: Status = "Triage".parse().unwrap();
+    assert_eq!(triage.as_str(), "Triage");
+    assert!(matches!(triage, Status::Custom(_)));
+    assert_eq!(
+        "Done".parse::<task_patching::data::StatusName>(),
+        Err(StatusNameError::BuiltIn(Status::Done))
+    );
+    assert_eq!(
+        "triage".parse::<Status>(),
+        Err(StatusNameError::MustStartWithLetter)
+    );
+    assert_eq!(
+        "Won't".parse::<Status>(),
+        Err(StatusNameError::InvalidCharacter('\''))
+    );
+}
+
+/// Tickets can only refer to users from the directory.
+fn check_users(client: &TicketStoreClient) {
+    for (id, name) in [("alice", "Alice"), ("bob", "Bob")] {
+        client
+            .add_user(User {
+                id: user(id),
+                name: name.into(),
+            })
+            .unwrap();
+    }
+    assert_eq!(client.user(user("bob")).unwrap().unwrap().name, "Bob");
+    assert_eq!(client.user(user("carol")).unwrap(), None);
+
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+        reporter: Some(user("alice")),
+        assignee: Some(user("bob")),
+        watchers: BTreeSet::from([user("alice")]),
+        labels: BTreeSet::new(),
+        priority: Priority::default(),
+        due: None,
+    };
+    match client.insert(TicketDraft {
+        watchers: BTreeSet::from([user("carol")]),
+        ..draft.clone()
+    }) {
+        Err(ClientError::Validation(ValidationError::UnknownUser(e))) => {
+            assert_eq!(e, UnknownUserError(user("carol")))
+        }
+        other => panic!("Expected an unknown user, got {other:?}"),
+    }
+    let assigned = client.insert(draft.clone()).unwrap();
+    let unassigned = client
+        .insert(TicketDraft {
+            assignee: None,
+            ..draft.clone()
+        })
+        .unwrap();
+
+    let ticket = client.get(assigned).unwrap().unwrap();
+    assert_eq!(ticket.reporter, Some(user("alice")));
+    assert_eq!(ticket.assignee, Some(user("bob")));
+    assert_eq!(ticket.watchers, draft.watchers);
+
+    let bob = client.acting_as(user("bob"));
+    assert_eq!(bob.assigned_to_me().unwrap(), vec![ticket.clone()]);
+    let ids: Vec<_> = bob.unassigned().unwrap().iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![unassigned]);
+    assert!(matches!(
+        client.assigned_to_me(),
+        Err(ClientError::Validation(ValidationError::Anonymous))
+    ));
+
+    let patch = |assignee: &str| TicketPatch {
+        id: unassigned,
+        title: None,
+        description: None,
+        status: None,
+        assignee: Some(Some(user(assignee))),
+        watchers: Some(BTreeSet::from([user("alice"), user("bob")])),
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: None,
+    };
+    match client.update(patch("carol")) {
+        Err(ClientError::Validation(ValidationError::UnknownUser(e))) => {
+            assert_eq!(e, UnknownUserError(user("carol")))
+        }
+        other => panic!("Expected an unknown user, got {other:?}"),
+    }
+    bob.update(patch("bob")).unwrap();
+    assert_eq!(bob.assigned_to_me().unwrap().len(), 2);
+    assert_eq!(
+        client.history(unassigned).unwrap()[0].changes[1],
+        Change::Watchers {
+            old: BTreeSet::from([user("alice")]),
+            new: BTreeSet::from([user("alice"), user("bob")]),
+        }
+    );
+    assert_eq!(
+        client.as_of(unassigned, 0).unwrap().unwrap().watchers,
+        draft.watchers
+    );
+
+    // Clearing the assignee puts the ticket back with the unassigned ones.
+    bob.update(TicketPatch {
+        assignee: Some(None),
+        watchers: None,
+        ..patch("bob")
+    })
+    .unwrap();
+    assert_eq!(bob.assigned_to_me().unwrap().len(), 1);
+    let ids: Vec<_> = bob.unassigned().unwrap().iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![unassigned]);
+    assert_eq!(
+        client.history(unassigned).unwrap()[1].changes,
+        vec![Change::Assignee {
+            old: Some(user("bob")),
+            new: None
+        }]
+    );
+}
+
+#[test]
+fn validates_users() {
+    check_users(&launch(5));
+}
+
+fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+        reporter: None,
+        assignee: None,
+        watchers: BTreeSet::new(),
+        labels: BTreeSet::new(),
+        priority: Priority::default(),
+        due: None,
+    }
+}
+
+/// Page through, count and delete a handful of tickets.
+fn check_listing(client: &TicketStoreClient) {
+    let ids: Vec<_> = (0..5).map(|_| client.insert(draft()).unwrap()).collect();
+    for id in [ids[1], ids[3]] {
+        client
+            .update(TicketPatch {
+                id,
+                title: None,
+                description: None,
+                status: Some(Status::Done),
+                assignee: None,
+                watchers: None,
+                add_labels: BTreeSet::new(),
+                remove_labels: BTreeSet::new(),
+                priority: None,
+                due: None,
+                expected_version: None,
+            })
+            .unwrap();
+    }
+    assert_eq!(client.count(None).unwrap(), 5);
+    assert_eq!(client.count(Some(Status::Done)).unwrap(), 2);
+
+    // Collect the ids of every page, following `next`.
+    let pages = |status| {
+        let mut pages = Vec::new();
+        let mut page = Some(Page::first(NonZeroUsize::new(2).unwrap()));
+        while let Some(current) = page {
+            let listed = client.list(status, current).unwrap();
+            pages.push(listed.tickets.iter().map(|t| t.id).collect::<Vec<_>>());
+            page = listed.next;
+        }
+        pages
+    };
+    assert_eq!(
+        pages(None),
+        vec![vec![ids[0], ids[1]], vec![ids[2], ids[3]], vec![ids[4]]]
+    );
+    assert_eq!(
+        pages(Some(Status::ToDo)),
+        vec![vec![ids[0], ids[2]], vec![ids[4]]]
+    );
+
+    let deleted = client.delete(ids[2]).unwrap().unwrap();
+    assert_eq!(deleted.id, ids[2]);
+    assert_eq!(client.delete(ids[2]).unwrap(), None);
+    assert_eq!(client.get(ids[2]).unwrap(), None);
+    assert_eq!(client.count(Some(Status::ToDo)).unwrap(), 2);
+
+    // Deleting the newest ticket doesn't free its id.
+    client.delete(ids[4]).unwrap().unwrap();
+    assert!(client.insert(draft()).unwrap() > ids[4]);
+
+    // The history of a deleted ticket is kept, and ends with the deletion.
+    client
+        .add_user(User {
+            id: user("alice"),
+            name: "Alice".into(),
+        })
+        .unwrap();
+    client
+        .acting_as(user("alice"))
+        .delete(ids[1])
+        .unwrap()
+        .unwrap();
+    let history = client.history(ids[1]).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].revision, 2);
+    assert_eq!(history[1].author, Some(user("alice")));
+    assert_eq!(history[1].changes, vec![Change::Deleted]);
+}
+
+#[test]
+fn lists_and_deletes_tickets() {
+    check_listing(&launch(5));
+}
+
+fn label(label: &str) -> Label {
+    label.parse().unwrap()
+}
+
+/// Label, prioritise and schedule a few tickets, then triage them.
+fn check_triage(client: &TicketStoreClient) {
+    let now = SystemTime::now();
+    let day = Duration::from_secs(24 * 60 * 60);
+    let draft = |priority, due| TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+        reporter: None,
+        assignee: None,
+        watchers: BTreeSet::new(),
+        labels: BTreeSet::from([label("bug")]),
+        priority,
+        due,
+    };
+    let low = client
+        .insert(draft(Priority::Low, Some(now - day)))
+        .unwrap();
+    let critical = client.insert(draft(Priority::Critical, None)).unwrap();
+    let high_later = client
+        .insert(draft(Priority::High, Some(now + day)))
+        .unwrap();
+    let high_late = client
+        .insert(draft(Priority::High, Some(now - 2 * day)))
+        .unwrap();
+    let done = client
+        .insert(draft(Priority::High, Some(now - day)))
+        .unwrap();
+
+    let patch = |id| TicketPatch {
+        id,
+        title: None,
+        description: None,
+        status: None,
+        assignee: None,
+        watchers: None,
+        add_labels: BTreeSet::new(),
+        remove_labels: BTreeSet::new(),
+        priority: None,
+        due: None,
+        expected_version: None,
+    };
+    client
+        .update(TicketPatch {
+            status: Some(Status::Done),
+            ..patch(done)
+        })
+        .unwrap();
+    client
+        .update(TicketPatch {
+            add_labels: BTreeSet::from([label("ui"), label("regression")]),
+            remove_labels: BTreeSet::from([label("bug"), label("regression")]),
+            priority: Some(Priority::Medium),
+            ..patch(low)
+        })
+        .unwrap();
+    let ticket = client.get(low).unwrap().unwrap();
+    assert_eq!(ticket.labels, BTreeSet::from([label("ui")]));
+    assert_eq!(ticket.priority, Priority::Medium);
+    assert_eq!(
+        client.history(low).unwrap()[0].changes,
+        vec![
+            Change::Labels {
+                old: BTreeSet::from([label("bug")]),
+                new: BTreeSet::from([label("ui")]),
+            },
+            Change::Priority {
+                old: Priority::Low,
+                new: Priority::Medium,
+            },
+        ]
+    );
+
+    let ids = |filter, sort| -> Vec<_> {
+        let tickets = client.find(filter, sort).unwrap();
+        tickets.iter().map(|ticket| ticket.id).collect()
+    };
+    assert_eq!(
+        ids(TicketFilter::default(), SortBy::Priority),
+        vec![critical, high_late, done, high_later, low]
+    );
+    assert_eq!(
+        ids(
+            TicketFilter {
+                label: Some(label("bug")),
+                priority: Some(Priority::High),
+                ..TicketFilter::default()
+            },
+            SortBy::Id
+        ),
+        vec![critical, high_later, high_late, done]
+    );
+    let overdue: Vec<_> = client.overdue().unwrap().iter().map(|t| t.id).collect();
+    assert_eq!(overdue, vec![high_late, low]);
+
+    // Due dates can be cleared.
+    client
+        .update(TicketPatch {
+            due: Some(None),
+            ..patch(high_late)
+        })
+        .unwrap();
+    assert_eq!(client.get(high_late).unwrap().unwrap().due, None);
+    assert_eq!(
+        client.as_of(high_late, 0).unwrap().unwrap().due,
+        client.history(high_late).unwrap()[0]
+            .changes
+            .iter()
+            .find_map(|change| match change {
+                Change::Due { old, .. } => Some(*old),
+                _ => None,
+            })
+            .unwrap()
+    );
+}
+
+#[test]
+fn triages_tickets() {
+    check_triage(&launch(5));
+}
+
+/// Load the shared fixture dataset from several clients at once, as a busy deployment would.
+fn check_fixture_load(client: &TicketStoreClient, profile: SizeProfile) {
+    let tickets = fixtures::tickets(profile);
+    let assignees: BTreeSet<_> = tickets.iter().filter_map(|t| t.assignee.clone()).collect();
+    for id in assignees {
+        client
+            .add_user(User {
+                id: user(&id),
+                name: id,
+            })
+            .unwrap();
+    }
+
+    let chunk_size = tickets.len().div_ceil(4);
+    std::thread::scope(|scope| {
+        for chunk in tickets.chunks(chunk_size) {
+            let client = client.clone();
+            scope.spawn(move || {
+                for fixture in chunk {
+                    load(&client, fixture);
+                }
+            });
+        }
+    });
+
+    assert_eq!(client.count(None).unwrap(), tickets.len());
+    for (fixture_status, status) in [
+        (FixtureStatus::ToDo, Status::ToDo),
+        (FixtureStatus::InProgress, Status::InProgress),
+        (FixtureStatus::Done, Status::Done),
+    ] {
+        let expected = tickets
+            .iter()
+            .filter(|t| t.status == fixture_status)
+            .count();
+        assert_eq!(client.count(Some(status)).unwrap(), expected);
+    }
+}
+
+/// Send the request until the server isn't overloaded anymore.
+fn retry<T>(request: impl Fn() -> Result<T, ClientError>) -> T {
+    loop {
+        match request() {
+            Err(ClientError::Overloaded(_)) => std::thread::yield_now(),
+            result => return result.unwrap(),
+        }
+    }
+}
+
+/// Insert `fixture`, then move it to its status.
+fn load(client: &TicketStoreClient, fixture: &FixtureTicket) {
+    let id = retry(|| {
+        client.insert(TicketDraft {
+            title: fixture.title.clone().try_into().unwrap(),
+            description: fixture.description.clone().try_into().unwrap(),
+            assignee: fixture.assignee.as_deref().map(user),
+            ..draft()
+        })
+    });
+    let status = match fixture.status {
+        FixtureStatus::ToDo => return,
+        FixtureStatus::InProgress => Status::InProgress,
+        FixtureStatus::Done => Status::Done,
+    };
+    retry(|| {
+        client.update(TicketPatch {
+            id,
+            title: None,
+            description: None,
+            status: Some(status),
+            assignee: None,
+            watchers: None,
+            add_labels: BTreeSet::new(),
+            remove_labels: BTreeSet::new(),
+            priority: None,
+            due: None,
+            expected_version: None,
+        })
+    });
+}
+
+#[test]
+fn handles_the_fixture_load() {
+    check_fixture_load(&launch(5), SizeProfile::Medium);
+}
+
+mod sqlite {
+    use super::{draft, label, user};
+    use common::fixtures::SizeProfile;
+    use std::collections::BTreeSet;
+    use task_patching::data::{SortBy, Status, TicketDraft, TicketFilter, TicketPatch};
+    use task_patching::sqlite::{SqliteStore, StorageError};
+    use task_patching::users::User;
+    use task_patching::workflow::Workflow;
+    use task_patching::{launch_sqlite, serve, ClientError};
+    use ticket_fields::test_helpers::ticket_description;
+    use ticket_fields::TicketTitle;
+
+    #[test]
+    fn survives_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.db");
+
+        let client = launch_sqlite(&path, 5).unwrap();
+        let id = client.insert(draft()).unwrap();
+        client
+            .update(TicketPatch {
+                id,
+                title: Some(TicketTitle::try_from("New title").unwrap()),
+                description: None,
+                status: Some(Status::Done),
+                assignee: None,
+                watchers: None,
+                add_labels: BTreeSet::new(),
+                remove_labels: BTreeSet::new(),
+                priority: None,
+                due: None,
+                expected_version: None,
+            })
+            .unwrap();
+        drop(client);
+
+        let client = launch_sqlite(&path, 5).unwrap();
+        let ticket = client.get(id).unwrap().unwrap();
+        assert_eq!(ticket.title.as_str(), "New title");
+        assert_eq!(ticket.description, ticket_description());
+        assert_eq!(ticket.status, Status::Done);
+        assert_ne!(client.insert(draft()).unwrap(), id);
+    }
+
+    #[test]
+    fn records_history() {
+        let dir = tempfile::tempdir().unwrap();
+        super::check_history(&launch_sqlite(dir.path().join("tickets.db"), 5).unwrap());
+    }
+
+    #[test]
+    fn rejects_stale_patches() {
+        let dir = tempfile::tempdir().unwrap();
+        super::check_conflicts(&launch_sqlite(dir.path().join("tickets.db"), 5).unwrap());
+    }
+
+    #[test]
+    fn enforces_the_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteStore::open(dir.path().join("tickets.db"))
+            .unwrap()
+            .with_workflow(Workflow::standard());
+        super::check_workflow(&serve(store, 5));
+    }
+
+    #[test]
+    fn follows_a_custom_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteStore::open(dir.path().join("tickets.db"))
+            .unwrap()
+            .with_workflow(super::triage_workflow());
+        super::check_custom_workflow(&serve(store, 5));
+    }
+
+    #[test]
+    fn handles_the_fixture_load() {
+        let dir = tempfile::tempdir().unwrap();
+        super::check_fixture_load(
+            &launch_sqlite(dir.path().join("tickets.db"), 5).unwrap(),
+            SizeProfile::Tiny,
+        );
+    }
+
+    #[test]
+    fn validates_users() {
+        let dir = tempfile::tempdir().unwrap();
+        super::check_users(&launch_sqlite(dir.path().join("tickets.db"), 5).unwrap());
+    }
+
+    #[test]
+    fn triages_tickets() {
+        let dir = tempfile::tempdir().unwrap();
+        super::check_triage(&launch_sqlite(dir.path().join("tickets.db"), 5).unwrap());
+    }
+
+    #[test]
+    fn lists_and_deletes_tickets() {
+        let dir = tempfile::tempdir().unwrap();
+        super::check_listing(&launch_sqlite(dir.path().join("tickets.db"), 5).unwrap());
+    }
+
+    #[test]
+    fn finds_tickets_with_their_watchers_and_labels() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SqliteStore::open(dir.path().join("tickets.db")).unwrap();
+        for id in ["alice", "bob"] {
+            store
+                .add_user(User {
+                    id: user(id),
+                    name: id.into(),
+                })
+                .unwrap();
+        }
+        let ids: Vec<_> = [
+            (vec!["alice"], vec!["bug"]),
+            (vec![], vec![]),
+            (vec!["alice", "bob"], vec!["ui", "bug"]),
+        ]
+        .into_iter()
+        .map(|(watchers, labels)| {
+            store
+                .add_ticket(TicketDraft {
+                    watchers: watchers.into_iter().map(user).collect(),
+                    labels: labels.into_iter().map(label).collect(),
+                    ..draft()
+                })
+                .unwrap()
+        })
+        .collect();
+
+        let found = store.find(&TicketFilter::default(), SortBy::Id).unwrap();
+        let expected: Vec<_> = ids
+            .iter()
+            .map(|id| store.get(*id).unwrap().unwrap())
+            .collect();
+        assert_eq!(found, expected);
+        assert_eq!(
+            found[2].watchers,
+            BTreeSet::from([user("alice"), user("bob")])
+        );
+        assert_eq!(found[2].labels, BTreeSet::from([label("bug"), label("ui")]));
+    }
+
+    #[test]
+    fn invalid_rows_are_typed_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.db");
+        let mut store = SqliteStore::open(&path).unwrap();
+        let id = store.add_ticket(draft()).unwrap();
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute("UPDATE tickets SET title = ''", [])
+            .unwrap();
+        assert!(matches!(
+            store.get(id),
+            Err(StorageError::InvalidTitle { id: found, .. }) if found == id
+        ));
+
+        connection
+            .execute("UPDATE tickets SET title = 'Fine', status = 'lost'", [])
+            .unwrap();
+        let client = launch_sqlite(&path, 5).unwrap();
+        assert!(matches!(
+            client.get(id),
+            Err(ClientError::Storage(StorageError::InvalidStatus { .. }))
+        ));
+    }
+
+    #[test]
+    fn rejects_newer_schemas() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.db");
+        SqliteStore::open(&path).unwrap();
+        // Opening again must not re-run the migrations.
+        SqliteStore::open(&path).unwrap();
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection.pragma_update(None, "user_version", 99).unwrap();
+        assert!(matches!(
+            SqliteStore::open(&path),
+            Err(StorageError::UnsupportedSchema { found: 99, .. })
+        ));
+    }
+}
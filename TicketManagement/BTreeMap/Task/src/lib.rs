@@ -1,69 +1,177 @@
-// TODO: Replace `todo!()`s with the correct implementation.
-//  Implement `IntoIterator` for `&TicketStore`. The iterator should yield immutable
-//  references to the tickets, ordered by their `TicketId`.
-//  Implement additional traits on `TicketId` if needed.
+use search::SearchIndex;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::ops::{Deref, DerefMut, Index};
+use ticket_store::BTreeMapRepository;
 
-use std::collections::BTreeMap;
-use std::ops::{Index, IndexMut};
-use ticket_fields::{TicketDescription, TicketTitle};
+pub use page::{Cursor, Page};
+pub use query::{ParseQueryError, TextMatch, TicketQuery};
+pub use search::SearchQuery;
+pub use ticket_store::{Status, Ticket, TicketDraft, TicketId};
 
-#[derive(Clone)]
-pub struct TicketStore {
-    tickets: BTreeMap<TicketId, Ticket>,
-    counter: u64,
-}
-
-#[derive(Clone, Copy, Debug, PartialEq/* TODO */)]
-pub struct TicketId(u64);
-
-#[derive(Clone, Debug, PartialEq)]
-pub struct Ticket {
-    pub id: TicketId,
-    pub title: TicketTitle,
-    pub description: TicketDescription,
-    pub status: Status,
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct TicketDraft {
-    pub title: TicketTitle,
-    pub description: TicketDescription,
-}
+mod page;
+mod query;
+mod search;
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
-pub enum Status {
-    ToDo,
-    InProgress,
-    Done,
+/// A [`ticket_store::TicketStore`] that also indexes its tickets, for searches and
+/// [`by_status`](Self::by_status).
+#[derive(Clone, Default)]
+pub struct TicketStore {
+    tickets: ticket_store::TicketStore<BTreeMapRepository>,
+    index: SearchIndex,
+    statuses: HashMap<Status, BTreeSet<TicketId>>,
 }
 
 impl TicketStore {
     pub fn new() -> Self {
         Self {
-            tickets: todo!(),
-            counter: 0,
+            tickets: ticket_store::TicketStore::new(),
+            index: SearchIndex::default(),
+            statuses: HashMap::new(),
         }
     }
 
     pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
-        let id = TicketId(self.counter);
-        self.counter += 1;
-        let ticket = Ticket {
-            id,
-            title: ticket.title,
-            description: ticket.description,
-            status: Status::ToDo,
-        };
-        todo!()
+        let id = self.tickets.add_ticket(ticket);
+        self.add_to_indexes(id);
         id
     }
 
     pub fn get(&self, id: TicketId) -> Option<&Ticket> {
-        todo!()
+        self.tickets.get(id)
+    }
+
+    /// Edit a ticket: the indexes catch up with the edits when the returned guard is dropped.
+    pub fn get_mut(&mut self, id: TicketId) -> Option<TicketMut<'_>> {
+        let ticket = self.tickets.get_mut(id)?;
+        Some(TicketMut::new(ticket, &mut self.index, &mut self.statuses))
+    }
+
+    /// Like [`get_mut`](Self::get_mut), for a ticket that must exist.
+    ///
+    /// # Panics
+    ///
+    /// Like indexing, if there is no such ticket: the message tells whether it was deleted or
+    /// archived.
+    pub fn ticket_mut(&mut self, id: TicketId) -> TicketMut<'_> {
+        let ticket = &mut self.tickets[id];
+        TicketMut::new(ticket, &mut self.index, &mut self.statuses)
+    }
+
+    /// Delete a ticket for good, whether it is archived or not.
+    pub fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        if self.get(id).is_some() {
+            self.remove_from_indexes(id);
+        }
+        self.tickets.remove(id)
+    }
+
+    /// Hide a ticket from lookups, iteration and queries, without deleting it.
+    ///
+    /// Returns `false` if there is no such ticket, or if it is already archived.
+    pub fn archive(&mut self, id: TicketId) -> bool {
+        if self.get(id).is_none() {
+            return false;
+        }
+        self.remove_from_indexes(id);
+        self.tickets.archive(id)
+    }
+
+    /// Bring an archived ticket back.
+    pub fn restore(&mut self, id: TicketId) -> bool {
+        if !self.tickets.restore(id) {
+            return false;
+        }
+        self.add_to_indexes(id);
+        true
+    }
+
+    pub fn get_archived(&self, id: TicketId) -> Option<&Ticket> {
+        self.tickets.get_archived(id)
+    }
+
+    /// The tickets that are neither archived nor deleted, ordered by id.
+    pub fn iter(&self) -> std::collections::btree_map::Values<'_, TicketId, Ticket> {
+        self.tickets.repository().values()
+    }
+
+    /// Up to `limit` tickets in id order, starting right after `after`.
+    ///
+    /// Pass `None` to start from the beginning, then the returned [`Page::next`] until it
+    /// is `None`. Tickets added while paging show up on a later page, since ids only grow.
+    ///
+    /// # Panics
+    ///
+    /// If `limit` is zero.
+    pub fn page(&self, after: Option<Cursor>, limit: usize) -> Page<'_> {
+        let start = after.map_or(Unbounded, |cursor| Excluded(cursor.0));
+        Page::collect(
+            self.tickets
+                .repository()
+                .range((start, Unbounded))
+                .map(|(_, t)| t),
+            limit,
+        )
+    }
+
+    /// Like [`page`](Self::page), but from the newest ticket to the oldest.
+    pub fn page_rev(&self, before: Option<Cursor>, limit: usize) -> Page<'_> {
+        let end = before.map_or(Unbounded, |cursor| Excluded(cursor.0));
+        Page::collect(
+            self.tickets
+                .repository()
+                .range((Unbounded, end))
+                .rev()
+                .map(|(_, t)| t),
+            limit,
+        )
+    }
+
+    /// The tickets matching `query`, ordered by id.
+    pub fn query<'a>(&'a self, query: &'a TicketQuery) -> impl Iterator<Item = &'a Ticket> {
+        self.iter().filter(move |ticket| query.matches(ticket))
+    }
+
+    /// Full-text search over titles and descriptions, best matches first.
+    ///
+    /// See [`SearchQuery`] for the query syntax.
+    pub fn search(&self, query: &str) -> Vec<&Ticket> {
+        self.search_query(&SearchQuery::parse(query))
+    }
+
+    pub fn search_query(&self, query: &SearchQuery) -> Vec<&Ticket> {
+        self.index
+            .search(query)
+            .into_iter()
+            .map(|id| &self.tickets[id])
+            .collect()
     }
 
-    pub fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
-        todo!()
+    /// The tickets with the given status, ordered by id.
+    ///
+    /// Backed by a per-status index, so it only visits the matching tickets.
+    pub fn by_status(&self, status: Status) -> impl Iterator<Item = &Ticket> {
+        static NONE: BTreeSet<TicketId> = BTreeSet::new();
+
+        self.statuses
+            .get(&status)
+            .unwrap_or(&NONE)
+            .iter()
+            .map(|id| &self.tickets[*id])
+    }
+
+    fn add_to_indexes(&mut self, id: TicketId) {
+        let ticket = &self.tickets[id];
+        self.index.insert(ticket);
+        self.statuses.entry(ticket.status).or_default().insert(id);
+    }
+
+    fn remove_from_indexes(&mut self, id: TicketId) {
+        let status = self.tickets[id].status;
+        self.index.remove(id);
+        if let Some(ids) = self.statuses.get_mut(&status) {
+            ids.remove(&id);
+        }
     }
 }
 
@@ -71,7 +179,7 @@ impl Index<TicketId> for TicketStore {
     type Output = Ticket;
 
     fn index(&self, index: TicketId) -> &Self::Output {
-        self.get(index).unwrap()
+        &self.tickets[index]
     }
 }
 
@@ -83,16 +191,69 @@ impl Index<&TicketId> for TicketStore {
     }
 }
 
-impl IndexMut<TicketId> for TicketStore {
-    fn index_mut(&mut self, index: TicketId) -> &mut Self::Output {
-        self.get_mut(index).unwrap()
+/// A ticket borrowed for editing from a [`TicketStore`].
+///
+/// Dropping it re-indexes the ticket, so that searches and [`TicketStore::by_status`] see the
+/// edits right away.
+pub struct TicketMut<'a> {
+    ticket: &'a mut Ticket,
+    index: &'a mut SearchIndex,
+    statuses: &'a mut HashMap<Status, BTreeSet<TicketId>>,
+    // The status the ticket is filed under.
+    status: Status,
+}
+
+impl<'a> TicketMut<'a> {
+    fn new(
+        ticket: &'a mut Ticket,
+        index: &'a mut SearchIndex,
+        statuses: &'a mut HashMap<Status, BTreeSet<TicketId>>,
+    ) -> Self {
+        let status = ticket.status;
+        Self {
+            ticket,
+            index,
+            statuses,
+            status,
+        }
+    }
+}
+
+impl Deref for TicketMut<'_> {
+    type Target = Ticket;
+
+    fn deref(&self) -> &Self::Target {
+        self.ticket
+    }
+}
+
+impl DerefMut for TicketMut<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ticket
     }
 }
 
-impl IndexMut<&TicketId> for TicketStore {
-    fn index_mut(&mut self, index: &TicketId) -> &mut Self::Output {
-        &mut self[*index]
+impl Drop for TicketMut<'_> {
+    fn drop(&mut self) {
+        let id = self.ticket.id();
+        self.index.insert(self.ticket);
+        if self.ticket.status != self.status {
+            if let Some(ids) = self.statuses.get_mut(&self.status) {
+                ids.remove(&id);
+            }
+            self.statuses
+                .entry(self.ticket.status)
+                .or_default()
+                .insert(id);
+        }
     }
 }
 
-/* TODO */
+impl<'a> IntoIterator for &'a TicketStore {
+    type Item = &'a Ticket;
+    type IntoIter = std::collections::btree_map::Values<'a, TicketId, Ticket>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
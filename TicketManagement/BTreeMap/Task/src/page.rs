@@ -0,0 +1,57 @@
+use crate::{Ticket, TicketId};
+use std::fmt;
+use std::str::FromStr;
+use ticket_fields::ParseTicketIdError;
+
+/// Where a listing stopped, to resume it with [`TicketStore::page`](crate::TicketStore::page)
+/// or [`TicketStore::page_rev`](crate::TicketStore::page_rev).
+///
+/// A cursor remembers the last ticket returned rather than an offset, so tickets added or
+/// removed between two calls never cause another ticket to be skipped or returned twice.
+/// Its string form can be handed to clients, who should treat it as an opaque token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cursor(pub(crate) TicketId);
+
+/// A slice of a listing, and the cursor to fetch the next one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page<'a> {
+    pub tickets: Vec<&'a Ticket>,
+    /// `None` once the listing is complete.
+    pub next: Option<Cursor>,
+}
+
+impl<'a> Page<'a> {
+    pub(crate) fn collect(mut tickets: impl Iterator<Item = &'a Ticket>, limit: usize) -> Self {
+        assert_ne!(limit, 0, "A page must hold at least one ticket");
+        let page: Vec<&Ticket> = tickets.by_ref().take(limit).collect();
+        let next = match (page.last(), tickets.next()) {
+            (Some(last), Some(_)) => Some(Cursor(last.id())),
+            _ => None,
+        };
+        Self {
+            tickets: page,
+            next,
+        }
+    }
+}
+
+/// Start a listing right after (or, in reverse, right before) a known ticket.
+impl From<TicketId> for Cursor {
+    fn from(id: TicketId) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ParseTicketIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
@@ -0,0 +1,353 @@
+use crate::{Status, Ticket, TicketId};
+use std::ops::{Bound, RangeBounds};
+use ticket_fields::ParseTicketIdError;
+
+/// A filter over tickets, built from predicates combined with `and`, `or` and `!`.
+///
+/// ```
+/// # use task_btree_map::{Status, TicketQuery};
+/// let query = TicketQuery::status(Status::ToDo).and(TicketQuery::title_contains("crash"));
+/// assert_eq!(query, "status:todo AND title:~\"crash\"".parse().unwrap());
+/// ```
+///
+/// The text syntax is made of `field:value` predicates, combined with `AND`, `OR`, `NOT`
+/// and parentheses. `NOT` binds tighter than `AND`, which binds tighter than `OR`, and
+/// predicates next to each other are implicitly joined with `AND`. The fields are:
+///  - `status:todo`, `status:inprogress` or `status:done`;
+///  - `title:"..."` and `description:"..."` for an exact match, or `title:~"..."` and
+///    `description:~"..."` for a case-insensitive substring;
+///  - `id:TKT-3`, or a range such as `id:TKT-3..TKT-9`, `id:TKT-3..=TKT-9` or `id:..TKT-9`;
+///  - `assignee:alice`, or `assignee:none` for unassigned tickets.
+///
+/// Quoted text is taken literally: `title:"~crash"` matches the title `~crash`, and
+/// `assignee:"none"` a user named `none`.
+///
+/// An empty query matches every ticket.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TicketQuery {
+    Status(Status),
+    Title(TextMatch),
+    Description(TextMatch),
+    Ids(Bound<TicketId>, Bound<TicketId>),
+    /// `None` matches unassigned tickets.
+    Assignee(Option<String>),
+    And(Vec<TicketQuery>),
+    Or(Vec<TicketQuery>),
+    Not(Box<TicketQuery>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextMatch {
+    Equals(String),
+    /// Case-insensitive.
+    Contains(String),
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ParseQueryError {
+    #[error("The query ended unexpectedly")]
+    UnexpectedEnd,
+    #[error("Unexpected {0:?} in the query")]
+    UnexpectedToken(String),
+    #[error("Expected a `field:value` predicate, found {0:?}")]
+    ExpectedPredicate(String),
+    #[error("Unknown field {0:?}")]
+    UnknownField(String),
+    #[error("Unknown status {0:?}")]
+    UnknownStatus(String),
+    #[error("Only `title` and `description` support `:~`, not {0:?}")]
+    UnsupportedContains(String),
+    #[error("Unterminated quote in the query")]
+    UnterminatedQuote,
+    #[error(transparent)]
+    InvalidId(#[from] ParseTicketIdError),
+}
+
+impl TicketQuery {
+    /// A query that matches every ticket.
+    pub fn all() -> Self {
+        TicketQuery::And(Vec::new())
+    }
+
+    pub fn status(status: Status) -> Self {
+        TicketQuery::Status(status)
+    }
+
+    pub fn title_contains(text: &str) -> Self {
+        TicketQuery::Title(TextMatch::Contains(text.into()))
+    }
+
+    pub fn description_contains(text: &str) -> Self {
+        TicketQuery::Description(TextMatch::Contains(text.into()))
+    }
+
+    pub fn ids(range: impl RangeBounds<TicketId>) -> Self {
+        TicketQuery::Ids(range.start_bound().cloned(), range.end_bound().cloned())
+    }
+
+    pub fn assignee(assignee: &str) -> Self {
+        TicketQuery::Assignee(Some(assignee.into()))
+    }
+
+    pub fn unassigned() -> Self {
+        TicketQuery::Assignee(None)
+    }
+
+    pub fn and(self, other: TicketQuery) -> Self {
+        match self {
+            TicketQuery::And(mut queries) => {
+                queries.push(other);
+                TicketQuery::And(queries)
+            }
+            query => TicketQuery::And(vec![query, other]),
+        }
+    }
+
+    pub fn or(self, other: TicketQuery) -> Self {
+        match self {
+            TicketQuery::Or(mut queries) => {
+                queries.push(other);
+                TicketQuery::Or(queries)
+            }
+            query => TicketQuery::Or(vec![query, other]),
+        }
+    }
+
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        match self {
+            TicketQuery::Status(status) => ticket.status == *status,
+            TicketQuery::Title(text) => text.matches(ticket.title.as_str()),
+            TicketQuery::Description(text) => text.matches(ticket.description.as_str()),
+            TicketQuery::Ids(start, end) => (*start, *end).contains(&ticket.id()),
+            TicketQuery::Assignee(assignee) => ticket.assignee == *assignee,
+            TicketQuery::And(queries) => queries.iter().all(|q| q.matches(ticket)),
+            TicketQuery::Or(queries) => queries.iter().any(|q| q.matches(ticket)),
+            TicketQuery::Not(query) => !query.matches(ticket),
+        }
+    }
+}
+
+impl std::ops::Not for TicketQuery {
+    type Output = TicketQuery;
+
+    fn not(self) -> Self::Output {
+        match self {
+            TicketQuery::Not(query) => *query,
+            query => TicketQuery::Not(Box::new(query)),
+        }
+    }
+}
+
+impl TextMatch {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            TextMatch::Equals(expected) => text == expected,
+            TextMatch::Contains(needle) => text.to_lowercase().contains(&needle.to_lowercase()),
+        }
+    }
+}
+
+impl std::str::FromStr for TicketQuery {
+    type Err = ParseQueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?.into_iter().peekable(),
+        };
+        if parser.tokens.peek().is_none() {
+            return Ok(TicketQuery::all());
+        }
+        let query = parser.or()?;
+        match parser.tokens.next() {
+            None => Ok(query),
+            Some(token) => Err(ParseQueryError::UnexpectedToken(token.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Predicate(TicketQuery),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Open => f.write_str("("),
+            Token::Close => f.write_str(")"),
+            Token::And => f.write_str("AND"),
+            Token::Or => f.write_str("OR"),
+            Token::Not => f.write_str("NOT"),
+            Token::Predicate(query) => write!(f, "{query:?}"),
+        }
+    }
+}
+
+/// Recursive descent over the tokens, one method per precedence level.
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<TicketQuery, ParseQueryError> {
+        let mut query = self.and()?;
+        while matches!(self.tokens.peek(), Some(Token::Or)) {
+            self.tokens.next();
+            query = query.or(self.and()?);
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<TicketQuery, ParseQueryError> {
+        let mut query = self.not()?;
+        loop {
+            match self.tokens.peek() {
+                Some(Token::And) => {
+                    self.tokens.next();
+                }
+                Some(Token::Not | Token::Open | Token::Predicate(_)) => {}
+                _ => return Ok(query),
+            }
+            query = query.and(self.not()?);
+        }
+    }
+
+    fn not(&mut self) -> Result<TicketQuery, ParseQueryError> {
+        match self.tokens.next() {
+            Some(Token::Not) => Ok(!self.not()?),
+            Some(Token::Open) => {
+                let query = self.or()?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(query),
+                    Some(token) => Err(ParseQueryError::UnexpectedToken(token.to_string())),
+                    None => Err(ParseQueryError::UnexpectedEnd),
+                }
+            }
+            Some(Token::Predicate(query)) => Ok(query),
+            Some(token) => Err(ParseQueryError::UnexpectedToken(token.to_string())),
+            None => Err(ParseQueryError::UnexpectedEnd),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, ParseQueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                // A word runs until whitespace or a parenthesis, unless they are quoted.
+                let mut word = String::new();
+                // Where the first quote opened: operators before it are live, but `:` or
+                // `~` past it are just text.
+                let mut quoted_from = None;
+                while let Some(&c) = chars.peek() {
+                    if c == '"' {
+                        chars.next();
+                        quoted_from.get_or_insert(word.len());
+                        loop {
+                            match chars.next() {
+                                Some('"') => break,
+                                Some(c) => word.push(c),
+                                None => return Err(ParseQueryError::UnterminatedQuote),
+                            }
+                        }
+                    } else if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    } else {
+                        chars.next();
+                        word.push(c);
+                    }
+                }
+                let quoted = quoted_from.is_some();
+                tokens.push(match word.as_str() {
+                    "AND" if !quoted => Token::And,
+                    "OR" if !quoted => Token::Or,
+                    "NOT" if !quoted => Token::Not,
+                    _ => Token::Predicate(predicate(&word, quoted_from)?),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse a `field:value` word, whose text is quoted from byte `quoted_from` on, if at all.
+fn predicate(word: &str, quoted_from: Option<usize>) -> Result<TicketQuery, ParseQueryError> {
+    let unquoted = quoted_from.unwrap_or(word.len());
+    let (field, value) = word
+        .split_once(':')
+        .filter(|(field, _)| field.len() < unquoted)
+        .ok_or_else(|| ParseQueryError::ExpectedPredicate(word.into()))?;
+    // `title:"~x"` looks for a title equal to `~x`.
+    let (value, contains) = match value.strip_prefix('~') {
+        Some(value) if field.len() + 1 < unquoted => (value, true),
+        _ => (value, false),
+    };
+    let text = || {
+        if contains {
+            TextMatch::Contains(value.into())
+        } else {
+            TextMatch::Equals(value.into())
+        }
+    };
+    if contains && field != "title" && field != "description" {
+        return Err(ParseQueryError::UnsupportedContains(field.into()));
+    }
+    match field {
+        "status" => parse_status(value).map(TicketQuery::Status),
+        "title" => Ok(TicketQuery::Title(text())),
+        "description" => Ok(TicketQuery::Description(text())),
+        "id" => parse_ids(value),
+        "assignee" if value == "none" && quoted_from.is_none() => Ok(TicketQuery::unassigned()),
+        "assignee" => Ok(TicketQuery::assignee(value)),
+        _ => Err(ParseQueryError::UnknownField(field.into())),
+    }
+}
+
+fn parse_status(value: &str) -> Result<Status, ParseQueryError> {
+    let normalized: String = value
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_lowercase();
+    match normalized.as_str() {
+        "todo" => Ok(Status::ToDo),
+        "inprogress" => Ok(Status::InProgress),
+        "done" => Ok(Status::Done),
+        _ => Err(ParseQueryError::UnknownStatus(value.into())),
+    }
+}
+
+fn parse_ids(value: &str) -> Result<TicketQuery, ParseQueryError> {
+    let Some((start, end)) = value.split_once("..") else {
+        let id: TicketId = value.parse()?;
+        return Ok(TicketQuery::ids(id..=id));
+    };
+    let start = match start {
+        "" => Bound::Unbounded,
+        start => Bound::Included(start.parse()?),
+    };
+    let end = match end.strip_prefix('=') {
+        Some(end) => Bound::Included(end.parse()?),
+        None if end.is_empty() => Bound::Unbounded,
+        None => Bound::Excluded(end.parse()?),
+    };
+    Ok(TicketQuery::Ids(start, end))
+}
@@ -0,0 +1,202 @@
+use crate::{Ticket, TicketId};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A parsed full-text query.
+///
+/// The text syntax is deliberately small:
+///  - whitespace-separated terms must all match (`login bug`);
+///  - `OR` separates alternatives and binds looser than the implicit AND
+///    (`crash OR panic`, `login bug OR signin`);
+///  - double quotes match a phrase, i.e. consecutive terms (`"login page"`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchQuery {
+    Term(String),
+    Phrase(Vec<String>),
+    And(Vec<SearchQuery>),
+    Or(Vec<SearchQuery>),
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut alternatives = Vec::new();
+        let mut conjunction = Vec::new();
+        let mut rest = query;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            if let Some(quoted) = rest.strip_prefix('"') {
+                // An unterminated quote extends to the end of the query.
+                let (phrase, tail) = quoted.split_once('"').unwrap_or((quoted, ""));
+                let terms = tokenize(phrase);
+                if !terms.is_empty() {
+                    conjunction.push(SearchQuery::Phrase(terms));
+                }
+                rest = tail;
+                continue;
+            }
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '"')
+                .unwrap_or(rest.len());
+            let (word, tail) = rest.split_at(end);
+            if word == "OR" {
+                alternatives.push(Self::all(std::mem::take(&mut conjunction)));
+            } else {
+                conjunction.extend(tokenize(word).into_iter().map(SearchQuery::Term));
+            }
+            rest = tail;
+        }
+        alternatives.push(Self::all(conjunction));
+        alternatives.retain(|q| *q != SearchQuery::And(Vec::new()));
+        if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            SearchQuery::Or(alternatives)
+        }
+    }
+
+    fn all(mut queries: Vec<SearchQuery>) -> Self {
+        if queries.len() == 1 {
+            queries.pop().unwrap()
+        } else {
+            SearchQuery::And(queries)
+        }
+    }
+
+    fn terms(&self) -> Vec<&str> {
+        match self {
+            SearchQuery::Term(term) => vec![term.as_str()],
+            SearchQuery::Phrase(terms) => terms.iter().map(String::as_str).collect(),
+            SearchQuery::And(queries) | SearchQuery::Or(queries) => {
+                queries.iter().flat_map(SearchQuery::terms).collect()
+            }
+        }
+    }
+}
+
+/// Where each term occurs within one ticket.
+type Positions = HashMap<String, Vec<usize>>;
+/// The term positions of a ticket, and its total number of terms.
+type Document = (Positions, usize);
+
+/// An inverted index over ticket titles and descriptions.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SearchIndex {
+    /// term -> ticket -> positions of the term in the ticket.
+    postings: HashMap<String, BTreeMap<TicketId, Vec<usize>>>,
+    /// ticket -> number of terms in the ticket.
+    lengths: BTreeMap<TicketId, usize>,
+    /// ticket -> distinct terms in the ticket, to unindex it without a full scan.
+    terms: BTreeMap<TicketId, Vec<String>>,
+}
+
+impl SearchIndex {
+    /// Index `ticket`, replacing whatever was indexed for its id before.
+    pub(crate) fn insert(&mut self, ticket: &Ticket) {
+        self.remove(ticket.id());
+        let (positions, length) = document(ticket);
+        let terms = positions.keys().cloned().collect();
+        for (term, positions) in positions {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(ticket.id(), positions);
+        }
+        self.lengths.insert(ticket.id(), length);
+        self.terms.insert(ticket.id(), terms);
+    }
+
+    pub(crate) fn remove(&mut self, id: TicketId) {
+        self.lengths.remove(&id);
+        for term in self.terms.remove(&id).unwrap_or_default() {
+            if let Some(documents) = self.postings.get_mut(&term) {
+                documents.remove(&id);
+                if documents.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Rank the tickets matching `query` by TF-IDF, best match first.
+    pub(crate) fn search(&self, query: &SearchQuery) -> Vec<TicketId> {
+        let terms: BTreeSet<&str> = query.terms().into_iter().collect();
+
+        let candidates: BTreeSet<TicketId> = terms
+            .iter()
+            .filter_map(|term| self.postings.get(*term))
+            .flat_map(|documents| documents.keys().copied())
+            .collect();
+
+        let mut results: Vec<(TicketId, f64)> = Vec::new();
+        for id in candidates {
+            let lookup = |term: &str| self.postings.get(term).and_then(|d| d.get(&id));
+            if matches(query, &lookup) {
+                let length = self.lengths[&id];
+                results.push((id, self.score(&terms, &lookup, length)));
+            }
+        }
+
+        results.sort_by(|(a_id, a), (b_id, b)| b.total_cmp(a).then(a_id.cmp(b_id)));
+        results.into_iter().map(|(id, _)| id).collect()
+    }
+
+    fn score<'a>(
+        &self,
+        terms: &BTreeSet<&str>,
+        lookup: &impl Fn(&str) -> Option<&'a Vec<usize>>,
+        length: usize,
+    ) -> f64 {
+        let total = self.lengths.len();
+        terms
+            .iter()
+            .filter_map(|term| {
+                let occurrences = lookup(term)?.len();
+                let tf = occurrences as f64 / length.max(1) as f64;
+                let frequency = self.postings.get(*term).map_or(0, BTreeMap::len);
+                let idf = ((1 + total) as f64 / (1 + frequency) as f64).ln() + 1.0;
+                Some(tf * idf)
+            })
+            .sum()
+    }
+}
+
+fn matches<'a>(query: &SearchQuery, lookup: &impl Fn(&str) -> Option<&'a Vec<usize>>) -> bool {
+    match query {
+        SearchQuery::Term(term) => lookup(term).is_some(),
+        SearchQuery::Phrase(terms) => {
+            let Some(first) = lookup(&terms[0]) else {
+                return false;
+            };
+            first.iter().any(|&start| {
+                terms[1..].iter().enumerate().all(|(offset, term)| {
+                    lookup(term).is_some_and(|positions| positions.contains(&(start + offset + 1)))
+                })
+            })
+        }
+        SearchQuery::And(queries) => {
+            !queries.is_empty() && queries.iter().all(|q| matches(q, lookup))
+        }
+        SearchQuery::Or(queries) => queries.iter().any(|q| matches(q, lookup)),
+    }
+}
+
+fn document(ticket: &Ticket) -> Document {
+    let title = tokenize(ticket.title.as_str());
+    let description = tokenize(ticket.description.as_str());
+    let mut positions = Positions::new();
+    // Leave a gap between title and description, so that phrases don't span both.
+    let offsets = (0..title.len()).chain(title.len() + 1..);
+    for (term, position) in title.iter().chain(&description).zip(offsets) {
+        positions.entry(term.clone()).or_default().push(position);
+    }
+    (positions, title.len() + description.len())
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,311 @@
+use task_btree_map::*;
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
+
+#[test]
+fn works() {
+    let mut store = TicketStore::new();
+
+    let id = store.add_ticket(draft());
+    let ticket = &store[id];
+    assert_eq!(ticket.title, ticket_title());
+    assert_eq!(ticket.description, ticket_description());
+    assert_eq!(ticket.status, Status::ToDo);
+
+    store.ticket_mut(id).status = Status::InProgress;
+    assert_eq!(store[&id].status, Status::InProgress);
+}
+
+#[test]
+fn ids_are_human_friendly() {
+    let mut store = TicketStore::new();
+
+    let id = store.add_ticket(draft());
+    assert_eq!(id.to_string(), "TKT-0");
+    assert_eq!("TKT-0".parse::<TicketId>().unwrap(), id);
+    assert!(store.get("TKT-1".parse().unwrap()).is_none());
+}
+
+#[test]
+fn iterates_in_id_order() {
+    let mut store = TicketStore::new();
+
+    let ids: Vec<TicketId> = (0..12).map(|_| store.add_ticket(draft())).collect();
+    let iterated: Vec<TicketId> = (&store).into_iter().map(|t| t.id()).collect();
+    assert_eq!(ids, iterated);
+}
+
+fn draft_with(title: &str, description: &str) -> TicketDraft {
+    TicketDraft {
+        title: title.try_into().unwrap(),
+        description: description.try_into().unwrap(),
+    }
+}
+
+fn search_ids(store: &TicketStore, query: &str) -> Vec<TicketId> {
+    store.search(query).into_iter().map(|t| t.id()).collect()
+}
+
+#[test]
+fn search_ranks_by_relevance() {
+    let mut store = TicketStore::new();
+    let login = store.add_ticket(draft_with("Login bug", "The login page crashes on login"));
+    let other = store.add_ticket(draft_with("Signup bug", "Cannot sign up"));
+    let _unrelated = store.add_ticket(draft_with("Dark mode", "Add a dark theme"));
+
+    assert_eq!(search_ids(&store, "bug"), vec![other, login]);
+    assert_eq!(search_ids(&store, "login bug"), vec![login]);
+    assert_eq!(search_ids(&store, "LOGIN"), vec![login]);
+    assert!(search_ids(&store, "payments").is_empty());
+    assert!(search_ids(&store, "").is_empty());
+}
+
+#[test]
+fn search_supports_or_and_phrases() {
+    let mut store = TicketStore::new();
+    let a = store.add_ticket(draft_with("Login page crash", "Happens every time"));
+    let b = store.add_ticket(draft_with("Crash on page load", "Login works fine"));
+    let c = store.add_ticket(draft_with("Timeout", "Requests time out"));
+
+    assert_eq!(search_ids(&store, "\"login page\""), vec![a]);
+    let mut either = search_ids(&store, "timeout OR \"page load\"");
+    either.sort();
+    assert_eq!(either, vec![b, c]);
+    assert_eq!(
+        SearchQuery::parse("crash \"login page\" OR timeout"),
+        SearchQuery::Or(vec![
+            SearchQuery::And(vec![
+                SearchQuery::Term("crash".into()),
+                SearchQuery::Phrase(vec!["login".into(), "page".into()]),
+            ]),
+            SearchQuery::Term("timeout".into()),
+        ])
+    );
+}
+
+#[test]
+fn search_sees_edits() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft_with("Login bug", "Cannot log in"));
+
+    store.ticket_mut(id).title = "Checkout bug".try_into().unwrap();
+    assert!(search_ids(&store, "login").is_empty());
+    assert_eq!(search_ids(&store, "checkout"), vec![id]);
+
+    let other = store.add_ticket(draft_with("Checkout slow", "Takes ages"));
+    let mut results = search_ids(&store, "checkout");
+    results.sort();
+    assert_eq!(results, vec![id, other]);
+
+    store.get_mut(other).unwrap().description = "Login takes ages".try_into().unwrap();
+    assert_eq!(search_ids(&store, "login"), vec![other]);
+}
+
+fn status_ids(store: &TicketStore, status: Status) -> Vec<TicketId> {
+    store.by_status(status).map(|t| t.id()).collect()
+}
+
+#[test]
+fn by_status_tracks_edits() {
+    let mut store = TicketStore::new();
+    let ids: Vec<TicketId> = (0..4).map(|_| store.add_ticket(draft())).collect();
+    assert_eq!(status_ids(&store, Status::ToDo), ids);
+    assert!(status_ids(&store, Status::InProgress).is_empty());
+
+    store.ticket_mut(ids[1]).status = Status::InProgress;
+    assert_eq!(
+        status_ids(&store, Status::ToDo),
+        vec![ids[0], ids[2], ids[3]]
+    );
+    assert_eq!(status_ids(&store, Status::InProgress), vec![ids[1]]);
+
+    store.get_mut(ids[2]).unwrap().status = Status::InProgress;
+    store.ticket_mut(ids[1]).status = Status::Done;
+    assert_eq!(status_ids(&store, Status::ToDo), vec![ids[0], ids[3]]);
+    assert_eq!(status_ids(&store, Status::InProgress), vec![ids[2]]);
+    assert_eq!(status_ids(&store, Status::Done), vec![ids[1]]);
+}
+
+#[test]
+fn archived_tickets_are_hidden() {
+    let mut store = TicketStore::new();
+    let kept = store.add_ticket(draft_with("Login bug", "Cannot log in"));
+    let archived = store.add_ticket(draft_with("Login timeout", "Slow"));
+
+    assert!(store.archive(archived));
+    assert!(!store.archive(archived));
+    assert!(store.get(archived).is_none());
+    assert_eq!(store.get_archived(archived).unwrap().id(), archived);
+    assert_eq!(store.iter().map(|t| t.id()).collect::<Vec<_>>(), vec![kept]);
+    assert_eq!(search_ids(&store, "login"), vec![kept]);
+    assert_eq!(status_ids(&store, Status::ToDo), vec![kept]);
+
+    assert!(store.restore(archived));
+    assert!(store.get_archived(archived).is_none());
+    assert_eq!(status_ids(&store, Status::ToDo), vec![kept, archived]);
+}
+
+#[test]
+fn removed_tickets_are_gone() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft_with("Login bug", "Cannot log in"));
+    let archived = store.add_ticket(draft());
+    store.archive(archived);
+
+    store.ticket_mut(id).status = Status::Done;
+    assert_eq!(store.remove(id).unwrap().status, Status::Done);
+    assert!(store.remove(id).is_none());
+    assert!(store.remove(archived).is_some());
+    assert!(store.get_archived(archived).is_none());
+    assert_eq!(store.iter().count(), 0);
+    assert!(search_ids(&store, "login").is_empty());
+    assert_eq!(status_ids(&store, Status::Done), vec![]);
+}
+
+#[test]
+#[should_panic(expected = "Ticket TKT-0 has been deleted")]
+fn ticket_mut_on_deleted_ticket_panics() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    store.remove(id);
+    store.ticket_mut(id).status = Status::Done;
+}
+
+#[test]
+#[should_panic(expected = "Ticket TKT-0 is archived")]
+fn index_on_archived_ticket_panics() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    store.archive(id);
+    let _ = &store[id];
+}
+
+fn page_ids(page: &Page) -> Vec<TicketId> {
+    page.tickets.iter().map(|t| t.id()).collect()
+}
+
+#[test]
+fn pages_through_the_store() {
+    let mut store = TicketStore::new();
+    let ids: Vec<TicketId> = (0..25).map(|_| store.add_ticket(draft())).collect();
+
+    let first = store.page(None, 10);
+    assert_eq!(page_ids(&first), ids[..10]);
+    let second = store.page(first.next, 10);
+    assert_eq!(page_ids(&second), ids[10..20]);
+    let third = store.page(second.next, 10);
+    assert_eq!(page_ids(&third), ids[20..]);
+    assert_eq!(third.next, None);
+
+    let newest = store.page_rev(None, 10);
+    assert_eq!(
+        page_ids(&newest),
+        ids[15..].iter().rev().copied().collect::<Vec<_>>()
+    );
+    let oldest = store.page_rev(Some(ids[3].into()), 10);
+    assert_eq!(page_ids(&oldest), vec![ids[2], ids[1], ids[0]]);
+    assert_eq!(oldest.next, None);
+}
+
+#[test]
+fn paging_is_stable_under_concurrent_changes() {
+    let mut store = TicketStore::new();
+    let ids: Vec<TicketId> = (0..4).map(|_| store.add_ticket(draft())).collect();
+
+    let first = store.page(None, 2);
+    let cursor = first.next.unwrap();
+    // The cursor survives a round-trip through its string form.
+    let cursor: Cursor = cursor.to_string().parse().unwrap();
+
+    store.remove(ids[1]);
+    let added = store.add_ticket(draft());
+    let rest = store.page(Some(cursor), 10);
+    assert_eq!(page_ids(&rest), vec![ids[2], ids[3], added]);
+}
+
+fn query_ids(store: &TicketStore, query: &str) -> Vec<TicketId> {
+    let query: TicketQuery = query.parse().unwrap();
+    store.query(&query).map(|t| t.id()).collect()
+}
+
+#[test]
+fn query_combines_predicates() {
+    let mut store = TicketStore::new();
+    let crash = store.add_ticket(draft_with("App crash on login", "Stack trace attached"));
+    let slow = store.add_ticket(draft_with("Slow search", "Takes ages to CRASH"));
+    let done = store.add_ticket(draft_with("Crash in export", "Fixed"));
+    store.ticket_mut(slow).assignee = Some("alice".into());
+    store.ticket_mut(done).status = Status::Done;
+
+    let built = TicketQuery::status(Status::ToDo).and(TicketQuery::title_contains("crash"));
+    assert_eq!(
+        store.query(&built).map(|t| t.id()).collect::<Vec<_>>(),
+        vec![crash]
+    );
+    assert_eq!(
+        query_ids(&store, "status:todo AND title:~\"crash\""),
+        vec![crash]
+    );
+    assert_eq!(
+        query_ids(&store, "description:~crash OR status:done"),
+        vec![slow, done]
+    );
+    assert_eq!(
+        query_ids(&store, "NOT status:done assignee:none"),
+        vec![crash]
+    );
+    assert_eq!(query_ids(&store, "assignee:alice"), vec![slow]);
+    assert_eq!(query_ids(&store, "title:\"Slow search\""), vec![slow]);
+    assert_eq!(query_ids(&store, "id:TKT-1.. NOT (id:TKT-2)"), vec![slow]);
+    assert_eq!(query_ids(&store, "id:..=TKT-1"), vec![crash, slow]);
+    assert_eq!(query_ids(&store, ""), vec![crash, slow, done]);
+}
+
+#[test]
+fn quoted_query_text_is_literal() {
+    let mut store = TicketStore::new();
+    let tilde = store.add_ticket(draft_with("~crash", "Odd title"));
+    let crash = store.add_ticket(draft_with("App crash", "Stack trace attached"));
+    store.ticket_mut(crash).assignee = Some("none".into());
+
+    assert_eq!(query_ids(&store, "title:\"~crash\""), vec![tilde]);
+    assert_eq!(query_ids(&store, "title:~\"crash\""), vec![tilde, crash]);
+    assert_eq!(query_ids(&store, "assignee:\"none\""), vec![crash]);
+    assert_eq!(query_ids(&store, "assignee:none"), vec![tilde]);
+    assert_eq!(
+        "\"title:crash\"".parse::<TicketQuery>(),
+        Err(ParseQueryError::ExpectedPredicate("title:crash".into()))
+    );
+}
+
+#[test]
+fn query_syntax_errors() {
+    let parse = |query: &str| query.parse::<TicketQuery>().unwrap_err();
+    assert_eq!(parse("status:"), ParseQueryError::UnknownStatus("".into()));
+    assert_eq!(parse("status:todo AND"), ParseQueryError::UnexpectedEnd);
+    assert_eq!(parse("(status:todo"), ParseQueryError::UnexpectedEnd);
+    assert_eq!(
+        parse("status:todo)"),
+        ParseQueryError::UnexpectedToken(")".into())
+    );
+    assert_eq!(
+        parse("crash"),
+        ParseQueryError::ExpectedPredicate("crash".into())
+    );
+    assert_eq!(
+        parse("owner:bob"),
+        ParseQueryError::UnknownField("owner".into())
+    );
+    assert_eq!(
+        parse("status:~todo"),
+        ParseQueryError::UnsupportedContains("status".into())
+    );
+    assert_eq!(parse("title:\"crash"), ParseQueryError::UnterminatedQuote);
+    assert!(matches!(parse("id:42"), ParseQueryError::InvalidId(_)));
+}
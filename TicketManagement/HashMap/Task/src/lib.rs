@@ -1,17 +1,18 @@
-// TODO: Replace `todo!()`s with the correct implementation.
-//  Implement additional traits on `TicketId` if needed.
-
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Index, IndexMut};
 use ticket_fields::{TicketDescription, TicketTitle};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TicketStore {
     tickets: HashMap<TicketId, Ticket>,
+    // Hidden from iteration and lookups, but still readable through `get_archived`.
+    archived: HashMap<TicketId, Ticket>,
+    // Ids of removed tickets, so that lookups can tell them apart from ids never issued.
+    deleted: HashSet<TicketId>,
     counter: u64,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq/* TODO */)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct TicketId(u64);
 
 #[derive(Clone, Debug, PartialEq)]
@@ -38,7 +39,9 @@ pub enum Status {
 impl TicketStore {
     pub fn new() -> Self {
         Self {
-            tickets: todo!()
+            tickets: HashMap::new(),
+            archived: HashMap::new(),
+            deleted: HashSet::new(),
             counter: 0,
         }
     }
@@ -52,16 +55,66 @@ impl TicketStore {
             description: ticket.description,
             status: Status::ToDo,
         };
-        todo!()
+        self.tickets.insert(id, ticket);
         id
     }
 
     pub fn get(&self, id: TicketId) -> Option<&Ticket> {
-        todo!()
+        self.tickets.get(&id)
     }
 
     pub fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
-        todo!()
+        self.tickets.get_mut(&id)
+    }
+
+    /// Delete a ticket for good, whether it is archived or not.
+    pub fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        let ticket = self
+            .tickets
+            .remove(&id)
+            .or_else(|| self.archived.remove(&id))?;
+        self.deleted.insert(id);
+        Some(ticket)
+    }
+
+    /// Hide a ticket from lookups and iteration, without deleting it.
+    ///
+    /// Returns `false` if there is no such ticket, or if it is already archived.
+    pub fn archive(&mut self, id: TicketId) -> bool {
+        let Some(ticket) = self.tickets.remove(&id) else {
+            return false;
+        };
+        self.archived.insert(id, ticket);
+        true
+    }
+
+    /// Bring an archived ticket back.
+    pub fn restore(&mut self, id: TicketId) -> bool {
+        let Some(ticket) = self.archived.remove(&id) else {
+            return false;
+        };
+        self.tickets.insert(id, ticket);
+        true
+    }
+
+    pub fn get_archived(&self, id: TicketId) -> Option<&Ticket> {
+        self.archived.get(&id)
+    }
+
+    /// The tickets that are neither archived nor deleted, in no particular order.
+    pub fn iter(&self) -> std::collections::hash_map::Values<'_, TicketId, Ticket> {
+        self.tickets.values()
+    }
+
+    /// Explain why there is no ticket for `id`, for the panics of `Index` and `IndexMut`.
+    fn missing(&self, id: TicketId) -> String {
+        if self.deleted.contains(&id) {
+            format!("Ticket {id:?} has been deleted")
+        } else if self.archived.contains_key(&id) {
+            format!("Ticket {id:?} is archived, use `get_archived` to read it")
+        } else {
+            format!("There is no ticket with id {id:?}")
+        }
     }
 }
 
@@ -69,7 +122,8 @@ impl Index<TicketId> for TicketStore {
     type Output = Ticket;
 
     fn index(&self, index: TicketId) -> &Self::Output {
-        self.get(index).unwrap()
+        self.get(index)
+            .unwrap_or_else(|| panic!("{}", self.missing(index)))
     }
 }
 
@@ -83,6 +137,9 @@ impl Index<&TicketId> for TicketStore {
 
 impl IndexMut<TicketId> for TicketStore {
     fn index_mut(&mut self, index: TicketId) -> &mut Self::Output {
+        if self.get(index).is_none() {
+            panic!("{}", self.missing(index));
+        }
         self.get_mut(index).unwrap()
     }
 }
@@ -1,11 +1,13 @@
-// TODO: Implement `IndexMut<&TicketId>` and `IndexMut<TicketId>` for `TicketStore`.
-
 use std::ops::{Index, IndexMut};
 use ticket_fields::{TicketDescription, TicketTitle};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TicketStore {
     tickets: Vec<Ticket>,
+    // Hidden from iteration and lookups, but still readable through `get_archived`.
+    archived: Vec<Ticket>,
+    // Ids of removed tickets, so that lookups can tell them apart from ids never issued.
+    deleted: Vec<TicketId>,
     counter: u64,
 }
 
@@ -37,6 +39,8 @@ impl TicketStore {
     pub fn new() -> Self {
         Self {
             tickets: Vec::new(),
+            archived: Vec::new(),
+            deleted: Vec::new(),
             counter: 0,
         }
     }
@@ -57,13 +61,77 @@ impl TicketStore {
     pub fn get(&self, id: TicketId) -> Option<&Ticket> {
         self.tickets.iter().find(|&t| t.id == id)
     }
+
+    pub fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
+        self.tickets.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Delete a ticket for good, whether it is archived or not.
+    pub fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        let ticket = if let Some(position) = position(&self.tickets, id) {
+            self.tickets.remove(position)
+        } else {
+            self.archived.remove(position(&self.archived, id)?)
+        };
+        self.deleted.push(id);
+        Some(ticket)
+    }
+
+    /// Hide a ticket from lookups and iteration, without deleting it.
+    ///
+    /// Returns `false` if there is no such ticket, or if it is already archived.
+    pub fn archive(&mut self, id: TicketId) -> bool {
+        let Some(position) = position(&self.tickets, id) else {
+            return false;
+        };
+        let ticket = self.tickets.remove(position);
+        self.archived.push(ticket);
+        true
+    }
+
+    /// Bring an archived ticket back, at its original place in the store.
+    pub fn restore(&mut self, id: TicketId) -> bool {
+        let Some(position) = position(&self.archived, id) else {
+            return false;
+        };
+        let ticket = self.archived.remove(position);
+        // Ids are handed out in increasing order, so `tickets` is sorted by id.
+        let position = self.tickets.partition_point(|t| t.id.0 < id.0);
+        self.tickets.insert(position, ticket);
+        true
+    }
+
+    pub fn get_archived(&self, id: TicketId) -> Option<&Ticket> {
+        self.archived.iter().find(|&t| t.id == id)
+    }
+
+    /// The tickets that are neither archived nor deleted, in creation order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Ticket> {
+        self.tickets.iter()
+    }
+
+    /// Explain why there is no ticket for `id`, for the panics of `Index` and `IndexMut`.
+    fn missing(&self, id: TicketId) -> String {
+        if self.deleted.contains(&id) {
+            format!("Ticket {id:?} has been deleted")
+        } else if self.get_archived(id).is_some() {
+            format!("Ticket {id:?} is archived, use `get_archived` to read it")
+        } else {
+            format!("There is no ticket with id {id:?}")
+        }
+    }
+}
+
+fn position(tickets: &[Ticket], id: TicketId) -> Option<usize> {
+    tickets.iter().position(|t| t.id == id)
 }
 
 impl Index<TicketId> for TicketStore {
     type Output = Ticket;
 
     fn index(&self, index: TicketId) -> &Self::Output {
-        self.get(index).unwrap()
+        self.get(index)
+            .unwrap_or_else(|| panic!("{}", self.missing(index)))
     }
 }
 
@@ -75,6 +143,17 @@ impl Index<&TicketId> for TicketStore {
     }
 }
 
-/* TODO */
+impl IndexMut<TicketId> for TicketStore {
+    fn index_mut(&mut self, index: TicketId) -> &mut Self::Output {
+        if self.get(index).is_none() {
+            panic!("{}", self.missing(index));
+        }
+        self.get_mut(index).unwrap()
+    }
+}
 
-/* TODO */
+impl IndexMut<&TicketId> for TicketStore {
+    fn index_mut(&mut self, index: &TicketId) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
@@ -0,0 +1,66 @@
+use task_index_mut_trait::*;
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
+
+#[test]
+fn works() {
+    let mut store = TicketStore::new();
+
+    let id = store.add_ticket(draft());
+    let ticket = &store[id];
+    assert_eq!(ticket.title, ticket_title());
+    assert_eq!(ticket.description, ticket_description());
+    assert_eq!(ticket.status, Status::ToDo);
+
+    store[id].status = Status::InProgress;
+    assert_eq!(store[&id].status, Status::InProgress);
+}
+
+#[test]
+fn archived_tickets_are_hidden() {
+    let mut store = TicketStore::new();
+    let kept = store.add_ticket(draft());
+    let archived = store.add_ticket(draft());
+
+    assert!(store.archive(archived));
+    assert!(!store.archive(archived));
+    assert!(store.get(archived).is_none());
+    assert_eq!(store.get_archived(archived).unwrap().id, archived);
+    assert_eq!(store.iter().map(|t| t.id).collect::<Vec<_>>(), vec![kept]);
+
+    assert!(store.restore(archived));
+    assert!(store.get_archived(archived).is_none());
+    assert_eq!(
+        store.iter().map(|t| t.id).collect::<Vec<_>>(),
+        vec![kept, archived]
+    );
+}
+
+#[test]
+fn removed_tickets_are_gone() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    let archived = store.add_ticket(draft());
+    store.archive(archived);
+
+    assert_eq!(store.remove(id).unwrap().id, id);
+    assert!(store.remove(id).is_none());
+    assert!(store.remove(archived).is_some());
+    assert!(store.get_archived(archived).is_none());
+    assert_eq!(store.iter().count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Ticket TicketId(0) has been deleted")]
+fn index_mut_on_deleted_ticket_panics() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    store.remove(id);
+    store[id].status = Status::Done;
+}
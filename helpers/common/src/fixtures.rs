@@ -0,0 +1,276 @@
+//! Deterministic fake ticket data, shared by benchmarks and load tests.
+//!
+//! The same seed and [`SizeProfile`] always produce the same tickets, on every platform,
+//! so results from different crates can be compared against one another.
+use std::io::{self, Write};
+
+/// The seed used when a caller has no reason to pick a specific one.
+pub const DEFAULT_SEED: u64 = 0x5EED_7111_C4E7_0001;
+
+/// 2024-01-01T00:00:00Z, the creation time of the oldest generated ticket.
+const EPOCH: u64 = 1_704_067_200;
+
+const VERBS: &[&str] = &[
+    "Fix",
+    "Investigate",
+    "Refactor",
+    "Document",
+    "Implement",
+    "Remove",
+    "Speed up",
+    "Test",
+];
+const SUBJECTS: &[&str] = &[
+    "login flow",
+    "password reset",
+    "search results",
+    "export to CSV",
+    "notification emails",
+    "dashboard widgets",
+    "API rate limiter",
+    "billing page",
+    "user avatars",
+    "audit log",
+];
+const QUALIFIERS: &[&str] = &[
+    "",
+    "on mobile",
+    "for admins",
+    "after upgrade",
+    "in Safari",
+    "under load",
+    "for new users",
+];
+const SENTENCES: &[&str] = &[
+    "Reported by several customers this week.",
+    "Steps to reproduce are attached to the support thread.",
+    "This started happening after the last deploy.",
+    "We should add a regression test once it's fixed.",
+    "The logs show a timeout on the upstream service.",
+    "Not urgent, but it keeps coming up in standups.",
+    "Design has signed off on the proposed behaviour.",
+    "Blocked until the new schema has been rolled out.",
+];
+const ASSIGNEES: &[&str] = &["alice", "bob", "carol", "dave", "erin", "frank"];
+
+/// How many tickets to generate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeProfile {
+    Tiny,
+    Medium,
+    Stress,
+}
+
+impl SizeProfile {
+    pub fn ticket_count(self) -> usize {
+        match self {
+            SizeProfile::Tiny => 10,
+            SizeProfile::Medium => 1_000,
+            SizeProfile::Stress => 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureStatus {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+impl FixtureStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FixtureStatus::ToDo => "ToDo",
+            FixtureStatus::InProgress => "InProgress",
+            FixtureStatus::Done => "Done",
+        }
+    }
+}
+
+/// A generated ticket, using plain types so that every crate can map it onto its own model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureTicket {
+    pub title: String,
+    pub description: String,
+    pub status: FixtureStatus,
+    pub assignee: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+impl FixtureTicket {
+    /// Serialize the ticket as a single line of JSON, without the trailing newline.
+    pub fn to_json_line(&self) -> String {
+        let assignee = match &self.assignee {
+            Some(assignee) => json_string(assignee),
+            None => "null".into(),
+        };
+        format!(
+            r#"{{"title":{},"description":{},"status":{},"assignee":{},"created_at":{}}}"#,
+            json_string(&self.title),
+            json_string(&self.description),
+            json_string(self.status.as_str()),
+            assignee,
+            self.created_at
+        )
+    }
+}
+
+/// A seeded source of [`FixtureTicket`]s.
+pub struct FixtureGenerator {
+    state: u64,
+    clock: u64,
+}
+
+impl FixtureGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            clock: EPOCH,
+        }
+    }
+
+    pub fn ticket(&mut self) -> FixtureTicket {
+        let verb = self.pick(VERBS);
+        let subject = self.pick(SUBJECTS);
+        let qualifier = self.pick(QUALIFIERS);
+        let title = if qualifier.is_empty() {
+            format!("{verb} {subject}")
+        } else {
+            format!("{verb} {subject} {qualifier}")
+        };
+
+        let sentence_count = 1 + self.below(4);
+        let description = (0..sentence_count)
+            .map(|_| self.pick(SENTENCES))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let status = match self.below(10) {
+            0..=4 => FixtureStatus::ToDo,
+            5..=7 => FixtureStatus::InProgress,
+            _ => FixtureStatus::Done,
+        };
+        let assignee = match status {
+            FixtureStatus::ToDo if self.below(2) == 0 => None,
+            _ => Some(self.pick(ASSIGNEES).to_string()),
+        };
+
+        // Up to an hour between consecutive tickets, so timestamps are strictly increasing.
+        self.clock += 1 + self.below(3_600);
+
+        FixtureTicket {
+            title,
+            description,
+            status,
+            assignee,
+            created_at: self.clock,
+        }
+    }
+
+    pub fn tickets(&mut self, count: usize) -> Vec<FixtureTicket> {
+        (0..count).map(|_| self.ticket()).collect()
+    }
+
+    /// SplitMix64: tiny, fast and identical on every platform.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// The dataset for `profile`, generated from [`DEFAULT_SEED`].
+pub fn tickets(profile: SizeProfile) -> Vec<FixtureTicket> {
+    FixtureGenerator::new(DEFAULT_SEED).tickets(profile.ticket_count())
+}
+
+/// Write `tickets` as JSON lines.
+pub fn write_json_lines<'a>(
+    tickets: impl IntoIterator<Item = &'a FixtureTicket>,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    for ticket in tickets {
+        writeln!(writer, "{}", ticket.to_json_line())?;
+    }
+    Ok(())
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        assert_eq!(tickets(SizeProfile::Tiny), tickets(SizeProfile::Tiny));
+        assert_ne!(
+            FixtureGenerator::new(1).tickets(10),
+            FixtureGenerator::new(2).tickets(10)
+        );
+    }
+
+    #[test]
+    fn test_profile_sizes() {
+        assert_eq!(tickets(SizeProfile::Tiny).len(), 10);
+        assert_eq!(tickets(SizeProfile::Medium).len(), 1_000);
+    }
+
+    #[test]
+    fn test_tickets_are_valid() {
+        let tickets = tickets(SizeProfile::Medium);
+        for ticket in &tickets {
+            assert!(!ticket.title.is_empty() && ticket.title.chars().count() <= 50);
+            assert!(!ticket.description.is_empty() && ticket.description.chars().count() <= 500);
+        }
+        assert!(tickets
+            .windows(2)
+            .all(|w| w[0].created_at < w[1].created_at));
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let ticket = FixtureTicket {
+            title: "Fix \"login\"".into(),
+            description: "Line one\nLine two".into(),
+            status: FixtureStatus::InProgress,
+            assignee: None,
+            created_at: 1_704_067_201,
+        };
+        let mut output = Vec::new();
+        write_json_lines([&ticket], &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"title\":\"Fix \\\"login\\\"\",\"description\":\"Line one\\nLine two\",\
+             \"status\":\"InProgress\",\"assignee\":null,\"created_at\":1704067201}\n"
+        );
+    }
+}
@@ -1,20 +1,77 @@
+use crate::policy::{FieldPolicy, PolicyViolation};
+use crate::validation::LengthUnit;
+use std::convert::TryFrom;
+
 #[derive(Debug, PartialEq, Clone, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub struct TicketDescription(String);
 
 #[derive(Debug, thiserror::Error)]
 pub enum TicketDescriptionError {
     #[error("The description cannot be empty")]
     Empty,
-    #[error("The description cannot be longer than 500 bytes")]
-    TooLong,
+    #[error("The description must be at least {min} {unit} long, but it is {length} {unit} long")]
+    TooShort {
+        length: usize,
+        min: usize,
+        unit: LengthUnit,
+    },
+    #[error("The description cannot be longer than {max} {unit}, but it is {length} {unit} long")]
+    TooLong {
+        length: usize,
+        max: usize,
+        unit: LengthUnit,
+    },
+    #[error("The description cannot contain control characters (found {0:?})")]
+    ControlCharacter(char),
+    #[error("The description cannot contain {0:?}")]
+    DisallowedCharacter(char),
+    #[error("The description cannot contain the word {0:?}")]
+    ForbiddenWord(String),
+    #[error("The description cannot match the pattern {0:?}")]
+    ForbiddenPattern(String),
+}
+
+impl From<PolicyViolation> for TicketDescriptionError {
+    fn from(violation: PolicyViolation) -> Self {
+        match violation {
+            PolicyViolation::Empty => Self::Empty,
+            PolicyViolation::TooShort { length, min, unit } => Self::TooShort { length, min, unit },
+            PolicyViolation::TooLong { length, max, unit } => Self::TooLong { length, max, unit },
+            PolicyViolation::ControlCharacter(c) => Self::ControlCharacter(c),
+            PolicyViolation::DisallowedCharacter(c) => Self::DisallowedCharacter(c),
+            PolicyViolation::ForbiddenWord(word) => Self::ForbiddenWord(word),
+            PolicyViolation::ForbiddenPattern(pattern) => Self::ForbiddenPattern(pattern),
+        }
+    }
+}
+
+impl TicketDescription {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Validate `value` against a custom [`FieldPolicy`] rather than the default one.
+    pub fn parse_with(policy: &FieldPolicy, value: &str) -> Result<Self, TicketDescriptionError> {
+        Ok(Self(policy.check(value)?))
+    }
 }
 
 impl TryFrom<String> for TicketDescription {
     type Error = TicketDescriptionError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        validate(&value)?;
-        Ok(Self(value))
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<TicketDescription> for String {
+    fn from(value: TicketDescription) -> Self {
+        value.0
     }
 }
 
@@ -22,18 +79,7 @@ impl TryFrom<&str> for TicketDescription {
     type Error = TicketDescriptionError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        validate(value)?;
-        Ok(Self(value.to_string()))
-    }
-}
-
-fn validate(description: &str) -> Result<(), TicketDescriptionError> {
-    if description.is_empty() {
-        Err(TicketDescriptionError::Empty)
-    } else if description.len() > 500 {
-        Err(TicketDescriptionError::TooLong)
-    } else {
-        Ok(())
+        Self::parse_with(&FieldPolicy::description(), value)
     }
 }
 
@@ -61,7 +107,22 @@ mod tests {
         let err = TicketDescription::try_from(overly_long_description()).unwrap_err();
         assert_eq!(
             err.to_string(),
-            "The description cannot be longer than 500 bytes"
+            "The description cannot be longer than 500 characters, but it is 844 characters long"
+        );
+    }
+
+    #[test]
+    fn test_try_from_multiline_string() {
+        let description = TicketDescription::try_from("Steps:\n\t1. Log in\r\n").unwrap();
+        assert_eq!(description.0, "Steps:\n\t1. Log in");
+    }
+
+    #[test]
+    fn test_try_from_control_character() {
+        let err = TicketDescription::try_from("A\u{0}description").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The description cannot contain control characters (found '\\0')"
         );
     }
 
@@ -70,4 +131,23 @@ mod tests {
         let description = TicketDescription::try_from("A description").unwrap();
         assert_eq!(description.0, "A description");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let description = TicketDescription::try_from("A description").unwrap();
+        let json = serde_json::to_string(&description).unwrap();
+        assert_eq!(json, "\"A description\"");
+        assert_eq!(
+            serde_json::from_str::<TicketDescription>(&json).unwrap(),
+            description
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_invalid() {
+        let err = serde_json::from_str::<TicketDescription>("\"\"").unwrap_err();
+        assert_eq!(err.to_string(), "The description cannot be empty");
+    }
 }
@@ -0,0 +1,265 @@
+use crate::InlineStr;
+use std::fmt;
+use std::str::FromStr;
+
+/// A short project identifier, such as `PROJ`.
+///
+/// Keys are 1 to [`MAX_LENGTH`](Self::MAX_LENGTH) uppercase ASCII letters or digits,
+/// starting with a letter. They are stored inline so that [`TicketId`] can stay `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProjectKey(InlineStr<{ ProjectKey::MAX_LENGTH }>);
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ProjectKeyError {
+    #[error("The project key cannot be empty")]
+    Empty,
+    #[error(
+        "The project key cannot be longer than {} characters",
+        ProjectKey::MAX_LENGTH
+    )]
+    TooLong,
+    #[error("The project key must start with an uppercase letter")]
+    MustStartWithLetter,
+    #[error("The project key can only contain uppercase letters and digits (found {0:?})")]
+    InvalidCharacter(char),
+}
+
+impl ProjectKey {
+    /// The maximum length of a key, in ASCII characters.
+    pub const MAX_LENGTH: usize = 8;
+
+    /// The project used for tickets created without an explicit one.
+    pub const DEFAULT: ProjectKey = ProjectKey(InlineStr::new("TKT").unwrap());
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<&str> for ProjectKey {
+    type Error = ProjectKeyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let first = value.chars().next().ok_or(ProjectKeyError::Empty)?;
+        if !first.is_ascii_uppercase() {
+            return Err(ProjectKeyError::MustStartWithLetter);
+        }
+        if let Some(c) = value
+            .chars()
+            .find(|c| !c.is_ascii_uppercase() && !c.is_ascii_digit())
+        {
+            return Err(ProjectKeyError::InvalidCharacter(c));
+        }
+        InlineStr::new(value)
+            .map(Self)
+            .ok_or(ProjectKeyError::TooLong)
+    }
+}
+
+impl FromStr for ProjectKey {
+    type Err = ProjectKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl Default for ProjectKey {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl fmt::Display for ProjectKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for ProjectKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProjectKey({})", self.as_str())
+    }
+}
+
+/// The identifier of a ticket, rendered as `PROJ-123`.
+///
+/// Ids are ordered by project first and by number second, so all the tickets of a project
+/// are sorted by creation order in a `BTreeMap<TicketId, _>`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct TicketId {
+    project: ProjectKey,
+    number: u64,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ParseTicketIdError {
+    #[error("A ticket id must look like `PROJ-123`")]
+    MissingSeparator,
+    #[error(transparent)]
+    InvalidProject(#[from] ProjectKeyError),
+    #[error(
+        "The ticket number must be a non-negative integer without leading zeros (found {0:?})"
+    )]
+    InvalidNumber(String),
+}
+
+impl TicketId {
+    /// A ticket id in the [default project](ProjectKey::DEFAULT).
+    pub fn new(number: u64) -> Self {
+        Self::with_project(ProjectKey::DEFAULT, number)
+    }
+
+    pub fn with_project(project: ProjectKey, number: u64) -> Self {
+        Self { project, number }
+    }
+
+    pub fn project(&self) -> ProjectKey {
+        self.project
+    }
+
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+}
+
+impl FromStr for TicketId {
+    type Err = ParseTicketIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (project, number) = s
+            .split_once('-')
+            .ok_or(ParseTicketIdError::MissingSeparator)?;
+        let project = project.parse()?;
+        // `u64::from_str` would also accept a leading `+`. Leading zeros are rejected so
+        // that every id has a single spelling: `TKT-007` would otherwise be `TKT-7`.
+        let canonical = match number.as_bytes() {
+            [] => false,
+            [b'0', _, ..] => false,
+            digits => digits.iter().all(|b| b.is_ascii_digit()),
+        };
+        if !canonical {
+            return Err(ParseTicketIdError::InvalidNumber(number.to_string()));
+        }
+        let number = number
+            .parse()
+            .map_err(|_| ParseTicketIdError::InvalidNumber(number.to_string()))?;
+        Ok(Self::with_project(project, number))
+    }
+}
+
+impl TryFrom<&str> for TicketId {
+    type Error = ParseTicketIdError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for TicketId {
+    type Error = ParseTicketIdError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TicketId> for String {
+    fn from(value: TicketId) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for TicketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.project, self.number)
+    }
+}
+
+impl fmt::Debug for TicketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TicketId({self})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let id = TicketId::with_project("PROJ".parse().unwrap(), 123);
+        assert_eq!(id.to_string(), "PROJ-123");
+        assert_eq!("PROJ-123".parse::<TicketId>().unwrap(), id);
+        assert_eq!(TicketId::new(7).to_string(), "TKT-7");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "PROJ123".parse::<TicketId>(),
+            Err(ParseTicketIdError::MissingSeparator)
+        );
+        assert_eq!(
+            "proj-1".parse::<TicketId>(),
+            Err(ParseTicketIdError::InvalidProject(
+                ProjectKeyError::MustStartWithLetter
+            ))
+        );
+        assert_eq!(
+            "PROJECTXY-1".parse::<TicketId>(),
+            Err(ParseTicketIdError::InvalidProject(ProjectKeyError::TooLong))
+        );
+        assert_eq!(
+            "P_1-1".parse::<TicketId>(),
+            Err(ParseTicketIdError::InvalidProject(
+                ProjectKeyError::InvalidCharacter('_')
+            ))
+        );
+        assert_eq!(
+            "PROJ-+1".parse::<TicketId>(),
+            Err(ParseTicketIdError::InvalidNumber("+1".into()))
+        );
+        assert_eq!(
+            "PROJ-".parse::<TicketId>().unwrap_err().to_string(),
+            "The ticket number must be a non-negative integer without leading zeros (found \"\")"
+        );
+        assert_eq!(
+            "TKT-007".parse::<TicketId>(),
+            Err(ParseTicketIdError::InvalidNumber("007".into()))
+        );
+        assert_eq!("TKT-0".parse::<TicketId>(), Ok(TicketId::new(0)));
+        assert_eq!(
+            ProjectKeyError::TooLong.to_string(),
+            format!(
+                "The project key cannot be longer than {} characters",
+                ProjectKey::MAX_LENGTH
+            )
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut ids = vec![TicketId::new(10), TicketId::new(2), TicketId::new(1)];
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![TicketId::new(1), TicketId::new(2), TicketId::new(10)]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let id = TicketId::new(42);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"TKT-42\"");
+        assert_eq!(serde_json::from_str::<TicketId>(&json).unwrap(), id);
+        assert!(serde_json::from_str::<TicketId>("\"TKT-x\"").is_err());
+    }
+}
@@ -0,0 +1,59 @@
+/// An ASCII string of up to `N` bytes, stored inline so that the types built on it can stay
+/// `Copy`.
+///
+/// It doesn't validate anything beyond the length: that is left to the types wrapping it,
+/// such as [`ProjectKey`](crate::ProjectKey).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InlineStr<const N: usize> {
+    bytes: [u8; N],
+    len: u8,
+}
+
+impl<const N: usize> InlineStr<N> {
+    /// Store `value`, if it is ASCII and at most `N` bytes long.
+    pub const fn new(value: &str) -> Option<Self> {
+        let value = value.as_bytes();
+        if value.len() > N || value.len() > u8::MAX as usize || !value.is_ascii() {
+            return None;
+        }
+        let mut bytes = [0; N];
+        let mut i = 0;
+        while i < value.len() {
+            bytes[i] = value[i];
+            i += 1;
+        }
+        Some(Self {
+            bytes,
+            len: value.len() as u8,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ever built from ASCII.
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holds_up_to_its_capacity() {
+        assert_eq!(InlineStr::<4>::new("ABCD").unwrap().as_str(), "ABCD");
+        assert_eq!(InlineStr::<4>::new("").unwrap().as_str(), "");
+        assert!(InlineStr::<4>::new("ABCDE").is_none());
+    }
+
+    #[test]
+    fn rejects_non_ascii() {
+        assert!(InlineStr::<8>::new("Café").is_none());
+    }
+
+    #[test]
+    fn orders_like_its_contents() {
+        let key = |s| InlineStr::<4>::new(s).unwrap();
+        assert!(key("AB") < key("B"));
+        assert!(key("A") < key("AB"));
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The maximum length of a [`Label`], in characters.
+const LABEL_MAX_LENGTH: usize = 32;
+
+/// A tag attached to a ticket, such as `bug` or `area:billing`.
+///
+/// Labels are 1 to 32 characters long and cannot contain whitespace or control characters.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Label(String);
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum LabelError {
+    #[error("The label cannot be empty")]
+    Empty,
+    #[error("The label cannot be longer than {LABEL_MAX_LENGTH} characters")]
+    TooLong,
+    #[error("The label cannot contain whitespace or control characters (found {0:?})")]
+    InvalidCharacter(char),
+}
+
+impl Label {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<&str> for Label {
+    type Error = LabelError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(LabelError::Empty);
+        }
+        if let Some(c) = value.chars().find(|c| c.is_whitespace() || c.is_control()) {
+            return Err(LabelError::InvalidCharacter(c));
+        }
+        if value.chars().count() > LABEL_MAX_LENGTH {
+            return Err(LabelError::TooLong);
+        }
+        Ok(Self(value.to_string()))
+    }
+}
+
+impl TryFrom<String> for Label {
+    type Error = LabelError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl FromStr for Label {
+    type Err = LabelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl From<Label> for String {
+    fn from(value: Label) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Label({})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid() {
+        for label in ["bug", "area:billing", "P1", "née"] {
+            assert_eq!(label.parse::<Label>().unwrap().as_str(), label);
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!("".parse::<Label>(), Err(LabelError::Empty));
+        assert_eq!(
+            "good first issue".parse::<Label>(),
+            Err(LabelError::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            "bug\n".parse::<Label>(),
+            Err(LabelError::InvalidCharacter('\n'))
+        );
+        assert_eq!("é".repeat(33).parse::<Label>(), Err(LabelError::TooLong));
+        assert!("é".repeat(32).parse::<Label>().is_ok());
+        assert_eq!(
+            LabelError::TooLong.to_string(),
+            "The label cannot be longer than 32 characters"
+        );
+    }
+}
@@ -1,6 +1,20 @@
 mod description;
+mod id;
+mod inline;
+mod label;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+mod policy;
 pub mod test_helpers;
 mod title;
+mod user;
+mod validation;
 
-pub use description::TicketDescription;
-pub use title::TicketTitle;
+pub use description::{TicketDescription, TicketDescriptionError};
+pub use id::{ParseTicketIdError, ProjectKey, ProjectKeyError, TicketId};
+pub use inline::InlineStr;
+pub use label::{Label, LabelError};
+pub use policy::{Charset, FieldPolicy, LengthBoundsError};
+pub use title::{TicketTitle, TicketTitleError};
+pub use user::{UserId, UserIdError};
+pub use validation::LengthUnit;
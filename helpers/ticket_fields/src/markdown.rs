@@ -0,0 +1,288 @@
+use crate::{TicketDescription, TicketDescriptionError};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd, TextMergeStream};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Matches `@handle`, unless the `@` is part of a word (e.g. an e-mail address).
+static MENTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|[^\w@])@(\w[\w-]*)").unwrap());
+
+/// URL schemes that are safe to keep in rendered links and images.
+const SAFE_SCHEMES: [&str; 3] = ["http:", "https:", "mailto:"];
+
+/// A ticket description written in (CommonMark) markdown.
+///
+/// Raw HTML is rejected at construction time, and rendering to HTML goes through
+/// [`MarkdownDescription::to_html`], which strips anything that could run a script.
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub struct MarkdownDescription(TicketDescription);
+
+#[derive(Debug, thiserror::Error)]
+pub enum MarkdownDescriptionError {
+    #[error(transparent)]
+    Description(#[from] TicketDescriptionError),
+    #[error("The description cannot contain raw HTML (found {0:?})")]
+    RawHtml(String),
+}
+
+/// A `- [ ]` / `- [x]` item of a markdown checklist.
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub checked: bool,
+}
+
+impl MarkdownDescription {
+    pub fn description(&self) -> &TicketDescription {
+        &self.0
+    }
+
+    /// The destination of every link in the description, in document order.
+    pub fn links(&self) -> Vec<String> {
+        self.events()
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link { dest_url, .. }) => Some(dest_url.into_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every checklist item in the description, in document order.
+    pub fn checklist(&self) -> Vec<ChecklistItem> {
+        let mut items = Vec::new();
+        let mut current: Option<ChecklistItem> = None;
+        for event in self.events() {
+            match event {
+                Event::TaskListMarker(checked) => {
+                    current = Some(ChecklistItem {
+                        text: String::new(),
+                        checked,
+                    });
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some(item) = current.as_mut() {
+                        item.text.push_str(&text);
+                    }
+                }
+                Event::Start(Tag::List(_)) | Event::End(TagEnd::Item) => {
+                    items.extend(current.take());
+                }
+                _ => {}
+            }
+        }
+        items.extend(current);
+        items
+    }
+
+    /// The fraction of checklist items that are ticked, or `None` if there is no checklist.
+    pub fn checklist_completion(&self) -> Option<f64> {
+        let checklist = self.checklist();
+        if checklist.is_empty() {
+            return None;
+        }
+        let checked = checklist.iter().filter(|item| item.checked).count();
+        Some(checked as f64 / checklist.len() as f64)
+    }
+
+    /// The handles `@mentioned` in the description, without the leading `@`.
+    ///
+    /// Mentions inside code spans and code blocks are ignored.
+    pub fn mentions(&self) -> Vec<String> {
+        let mut mentions = Vec::new();
+        let mut in_code_block = false;
+        for event in self.events() {
+            match event {
+                Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+                Event::End(TagEnd::CodeBlock) => in_code_block = false,
+                Event::Text(text) if !in_code_block => {
+                    mentions.extend(
+                        MENTION
+                            .captures_iter(&text)
+                            .map(|captures| captures[1].to_string()),
+                    );
+                }
+                _ => {}
+            }
+        }
+        mentions
+    }
+
+    /// Render the description to HTML.
+    ///
+    /// Links and images pointing to anything other than `http(s)` or `mailto` URLs are
+    /// neutralized, so the output is safe to embed in a page.
+    pub fn to_html(&self) -> String {
+        let events = self.events().map(|event| match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: sanitize_url(dest_url),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Image {
+                link_type,
+                dest_url: sanitize_url(dest_url),
+                title,
+                id,
+            }),
+            // Rejected at construction time, but never trust the renderer with it.
+            Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+            event => event,
+        });
+        let mut output = String::new();
+        html::push_html(&mut output, events);
+        output
+    }
+
+    fn events(&self) -> impl Iterator<Item = Event<'_>> {
+        TextMergeStream::new(parse(self.0.as_str()))
+    }
+}
+
+impl TryFrom<TicketDescription> for MarkdownDescription {
+    type Error = MarkdownDescriptionError;
+
+    fn try_from(value: TicketDescription) -> Result<Self, Self::Error> {
+        if let Some(html) = parse(value.as_str()).find_map(|event| match event {
+            Event::Html(html) | Event::InlineHtml(html) => Some(html),
+            _ => None,
+        }) {
+            return Err(MarkdownDescriptionError::RawHtml(html.trim().to_string()));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl TryFrom<String> for MarkdownDescription {
+    type Error = MarkdownDescriptionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        TicketDescription::try_from(value)?.try_into()
+    }
+}
+
+impl TryFrom<&str> for MarkdownDescription {
+    type Error = MarkdownDescriptionError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        TicketDescription::try_from(value)?.try_into()
+    }
+}
+
+fn parse(text: &str) -> Parser<'_> {
+    Parser::new_ext(
+        text,
+        Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH,
+    )
+}
+
+fn sanitize_url(url: CowStr<'_>) -> CowStr<'_> {
+    let scheme = url.split_once(':').map(|(scheme, _)| scheme);
+    let is_relative = scheme.is_none_or(|scheme| scheme.contains(['/', '?', '#']));
+    if is_relative
+        || SAFE_SCHEMES.iter().any(|safe| {
+            url.get(..safe.len())
+                .is_some_and(|s| s.eq_ignore_ascii_case(safe))
+        })
+    {
+        url
+    } else {
+        CowStr::Borrowed("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_raw_html() {
+        let err = MarkdownDescription::try_from("Hello <script>alert(1)</script>").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The description cannot contain raw HTML (found \"<script>\")"
+        );
+
+        let err = MarkdownDescription::try_from("<div>\nblock\n</div>").unwrap_err();
+        assert!(matches!(err, MarkdownDescriptionError::RawHtml(_)));
+    }
+
+    #[test]
+    fn test_propagates_description_errors() {
+        let err = MarkdownDescription::try_from("").unwrap_err();
+        assert_eq!(err.to_string(), "The description cannot be empty");
+    }
+
+    #[test]
+    fn test_links() {
+        let description = MarkdownDescription::try_from(
+            "See [the docs](https://example.com) and <https://rust-lang.org>",
+        )
+        .unwrap();
+        assert_eq!(
+            description.links(),
+            vec!["https://example.com", "https://rust-lang.org"]
+        );
+    }
+
+    #[test]
+    fn test_checklist() {
+        let description = MarkdownDescription::try_from(
+            "Steps:\n\n- [x] Reproduce\n- [ ] Fix `login`\n- [ ] Test\n- Not a task",
+        )
+        .unwrap();
+        assert_eq!(
+            description.checklist(),
+            vec![
+                ChecklistItem {
+                    text: "Reproduce".into(),
+                    checked: true
+                },
+                ChecklistItem {
+                    text: "Fix login".into(),
+                    checked: false
+                },
+                ChecklistItem {
+                    text: "Test".into(),
+                    checked: false
+                },
+            ]
+        );
+        assert_eq!(description.checklist_completion(), Some(1.0 / 3.0));
+    }
+
+    #[test]
+    fn test_checklist_completion_without_checklist() {
+        let description = MarkdownDescription::try_from("Nothing to do").unwrap();
+        assert_eq!(description.checklist_completion(), None);
+    }
+
+    #[test]
+    fn test_mentions() {
+        let description = MarkdownDescription::try_from(
+            "@alice please sync with @bob-smith (cc me@example.com)\n\n```\n@ignored\n```",
+        )
+        .unwrap();
+        assert_eq!(description.mentions(), vec!["alice", "bob-smith"]);
+    }
+
+    #[test]
+    fn test_to_html() {
+        let description =
+            MarkdownDescription::try_from("**Bold** [click](javascript:alert(1)) [ok](/tickets/1)")
+                .unwrap();
+        assert_eq!(
+            description.to_html(),
+            "<p><strong>Bold</strong> <a href=\"\">click</a> <a href=\"/tickets/1\">ok</a></p>\n"
+        );
+    }
+}
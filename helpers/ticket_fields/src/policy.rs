@@ -0,0 +1,272 @@
+use crate::validation::{find_control_char, normalize, LengthUnit};
+use regex::Regex;
+
+/// The maximum length of a ticket title under the default policy.
+pub(crate) const TITLE_MAX_LENGTH: usize = 50;
+/// The maximum length of a ticket description under the default policy.
+pub(crate) const DESCRIPTION_MAX_LENGTH: usize = 500;
+
+/// The set of characters a field is allowed to contain.
+#[derive(Debug, Clone, Copy)]
+pub enum Charset {
+    /// Any character that is not a control character.
+    Any,
+    /// Printable ASCII only.
+    Ascii,
+    /// Characters accepted by a custom predicate.
+    Custom(fn(char) -> bool),
+}
+
+impl Charset {
+    fn allows(self, c: char) -> bool {
+        match self {
+            Charset::Any => true,
+            Charset::Ascii => c.is_ascii(),
+            Charset::Custom(predicate) => predicate(c),
+        }
+    }
+}
+
+/// A set of validation rules for a free-text ticket field.
+///
+/// The defaults used by `TryFrom` are available as [`FieldPolicy::title`] and
+/// [`FieldPolicy::description`]; deployments that need different rules can build their own
+/// and pass it to `TicketTitle::parse_with` or `TicketDescription::parse_with`.
+#[derive(Debug, Clone)]
+pub struct FieldPolicy {
+    min_length: usize,
+    max_length: usize,
+    length_unit: LengthUnit,
+    allow_line_breaks: bool,
+    charset: Charset,
+    forbidden_words: Vec<String>,
+    forbidden_patterns: Vec<Regex>,
+}
+
+/// A [`FieldPolicy`] was asked to accept fewer characters than it requires.
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[error("The minimum length ({min}) cannot be greater than the maximum length ({max})")]
+pub struct LengthBoundsError {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// The reason why a value was rejected by a [`FieldPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PolicyViolation {
+    Empty,
+    TooShort {
+        length: usize,
+        min: usize,
+        unit: LengthUnit,
+    },
+    TooLong {
+        length: usize,
+        max: usize,
+        unit: LengthUnit,
+    },
+    ControlCharacter(char),
+    DisallowedCharacter(char),
+    ForbiddenWord(String),
+    ForbiddenPattern(String),
+}
+
+impl FieldPolicy {
+    /// A policy accepting from `min_length` to `max_length` characters on a single line.
+    ///
+    /// Fails if no value could ever be accepted, i.e. if `min_length` is greater than
+    /// `max_length`.
+    pub fn new(min_length: usize, max_length: usize) -> Result<Self, LengthBoundsError> {
+        if min_length > max_length {
+            return Err(LengthBoundsError {
+                min: min_length,
+                max: max_length,
+            });
+        }
+        Ok(Self {
+            min_length,
+            ..Self::up_to(max_length)
+        })
+    }
+
+    /// A policy accepting 1 to `max_length` characters on a single line.
+    fn up_to(max_length: usize) -> Self {
+        Self {
+            min_length: 1,
+            max_length,
+            length_unit: LengthUnit::Graphemes,
+            allow_line_breaks: false,
+            charset: Charset::Any,
+            forbidden_words: Vec::new(),
+            forbidden_patterns: Vec::new(),
+        }
+    }
+
+    /// The default policy for ticket titles.
+    pub fn title() -> Self {
+        Self::up_to(TITLE_MAX_LENGTH)
+    }
+
+    /// The default policy for ticket descriptions.
+    pub fn description() -> Self {
+        Self::up_to(DESCRIPTION_MAX_LENGTH).allow_line_breaks(true)
+    }
+
+    pub fn length_unit(mut self, length_unit: LengthUnit) -> Self {
+        self.length_unit = length_unit;
+        self
+    }
+
+    pub fn allow_line_breaks(mut self, allow_line_breaks: bool) -> Self {
+        self.allow_line_breaks = allow_line_breaks;
+        self
+    }
+
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Reject values containing `word`, compared case-insensitively against whole words.
+    pub fn forbid_word(mut self, word: impl Into<String>) -> Self {
+        self.forbidden_words.push(word.into().to_lowercase());
+        self
+    }
+
+    /// Reject values matching the regular expression `pattern`.
+    pub fn forbid_pattern(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.forbidden_patterns.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Normalize `text` and check it against the policy, returning the value to be stored.
+    pub(crate) fn check(&self, text: &str) -> Result<String, PolicyViolation> {
+        let text = normalize(text);
+        if text.is_empty() {
+            return Err(PolicyViolation::Empty);
+        }
+        if let Some(c) = find_control_char(&text, self.allow_line_breaks) {
+            return Err(PolicyViolation::ControlCharacter(c));
+        }
+        if let Some(c) = text
+            .chars()
+            .find(|&c| !c.is_control() && !self.charset.allows(c))
+        {
+            return Err(PolicyViolation::DisallowedCharacter(c));
+        }
+        let length = self.length_unit.measure(&text);
+        if length < self.min_length {
+            return Err(PolicyViolation::TooShort {
+                length,
+                min: self.min_length,
+                unit: self.length_unit,
+            });
+        }
+        if length > self.max_length {
+            return Err(PolicyViolation::TooLong {
+                length,
+                max: self.max_length,
+                unit: self.length_unit,
+            });
+        }
+        if let Some(word) = text
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .find(|word| self.forbidden_words.contains(word))
+        {
+            return Err(PolicyViolation::ForbiddenWord(word));
+        }
+        if let Some(pattern) = self.forbidden_patterns.iter().find(|p| p.is_match(&text)) {
+            return Err(PolicyViolation::ForbiddenPattern(
+                pattern.as_str().to_string(),
+            ));
+        }
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_length() {
+        let policy = FieldPolicy::new(3, 10).unwrap();
+        assert_eq!(
+            policy.check("ab"),
+            Err(PolicyViolation::TooShort {
+                length: 2,
+                min: 3,
+                unit: LengthUnit::Graphemes
+            })
+        );
+        assert_eq!(policy.check("abc"), Ok("abc".to_string()));
+    }
+
+    #[test]
+    fn test_length_bounds() {
+        assert_eq!(
+            FieldPolicy::new(5, 4).unwrap_err(),
+            LengthBoundsError { min: 5, max: 4 }
+        );
+        assert!(FieldPolicy::new(4, 4).is_ok());
+    }
+
+    #[test]
+    fn test_length_unit() {
+        let policy = FieldPolicy::new(1, 4)
+            .unwrap()
+            .length_unit(LengthUnit::Bytes);
+        assert_eq!(
+            policy.check("äöü"),
+            Err(PolicyViolation::TooLong {
+                length: 6,
+                max: 4,
+                unit: LengthUnit::Bytes
+            })
+        );
+    }
+
+    #[test]
+    fn test_charset() {
+        let policy = FieldPolicy::new(1, 10).unwrap().charset(Charset::Ascii);
+        assert_eq!(
+            policy.check("Café"),
+            Err(PolicyViolation::DisallowedCharacter('é'))
+        );
+
+        let policy = FieldPolicy::new(1, 10)
+            .unwrap()
+            .charset(Charset::Custom(|c| c != '#'));
+        assert_eq!(
+            policy.check("Bug #1"),
+            Err(PolicyViolation::DisallowedCharacter('#'))
+        );
+    }
+
+    #[test]
+    fn test_forbidden_words() {
+        let policy = FieldPolicy::new(1, 50).unwrap().forbid_word("Urgent");
+        assert_eq!(
+            policy.check("This is URGENT!"),
+            Err(PolicyViolation::ForbiddenWord("urgent".to_string()))
+        );
+        assert!(policy.check("Not urgently needed").is_ok());
+    }
+
+    #[test]
+    fn test_forbidden_patterns() {
+        let policy = FieldPolicy::new(1, 50)
+            .unwrap()
+            .forbid_pattern(r"\b\d{16}\b")
+            .unwrap();
+        assert_eq!(
+            policy.check("Card 1234567812345678 declined"),
+            Err(PolicyViolation::ForbiddenPattern(r"\b\d{16}\b".to_string()))
+        );
+        assert!(FieldPolicy::new(1, 50)
+            .unwrap()
+            .forbid_pattern("(")
+            .is_err());
+    }
+}
@@ -1,6 +1,11 @@
 use crate::{TicketDescription, TicketTitle};
 use common::{valid_description, valid_title};
 
+#[cfg(feature = "quickcheck")]
+pub mod arbitrary;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+
 /// A function to generate a valid ticket title,
 /// for test purposes.
 pub fn ticket_title() -> TicketTitle {
@@ -0,0 +1,137 @@
+//! `quickcheck` generators producing valid and invalid ticket fields.
+//!
+//! They follow the same recipes as the `proptest` strategies in `strategies`: valid
+//! values at the boundary lengths and in between, mixing ASCII with multibyte characters.
+//! Valid values come from the `Arbitrary` impls of the field types themselves; invalid raw
+//! strings are wrapped in [`InvalidTitle`] and [`InvalidDescription`].
+use crate::policy::{DESCRIPTION_MAX_LENGTH, TITLE_MAX_LENGTH};
+use crate::{TicketDescription, TicketTitle};
+use quickcheck::{Arbitrary, Gen};
+
+/// A raw string rejected by `TicketTitle::try_from`.
+#[derive(Clone, Debug)]
+pub struct InvalidTitle(pub String);
+
+/// A raw string rejected by `TicketDescription::try_from`.
+#[derive(Clone, Debug)]
+pub struct InvalidDescription(pub String);
+
+/// A character between `low` and `high`, inclusive.
+fn char_between(g: &mut Gen, low: char, high: char) -> char {
+    let offset = u32::arbitrary(g) % (high as u32 - low as u32 + 1);
+    char::from_u32(low as u32 + offset).expect("the ranges used don't span surrogates")
+}
+
+/// A single, NFC-stable, non-whitespace grapheme cluster.
+fn grapheme(g: &mut Gen) -> char {
+    // ASCII is listed four times, to be picked as often as in the proptest strategy.
+    let ranges = [
+        ('!', '~'),
+        ('!', '~'),
+        ('!', '~'),
+        ('!', '~'),
+        ('À', 'ÿ'),
+        ('一', '龥'),
+        ('😀', '🙏'),
+    ];
+    let &(low, high) = g.choose(&ranges).unwrap();
+    char_between(g, low, high)
+}
+
+/// A string of exactly `length` graphemes, without leading or trailing whitespace.
+fn text_of_length(g: &mut Gen, length: usize) -> String {
+    (0..length)
+        .map(|i| {
+            let c = grapheme(g);
+            // Interior spaces count as a grapheme each, so they keep the length intact.
+            let space = u8::arbitrary(g) % 100 < 15;
+            if space && i != 0 && i != length - 1 {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// A number between `low` and `high`, inclusive.
+fn between(g: &mut Gen, low: usize, high: usize) -> usize {
+    low + usize::arbitrary(g) % (high - low + 1)
+}
+
+/// A string accepted by a field limited to `max` graphemes, biased towards the boundaries.
+fn valid_text(g: &mut Gen, max: usize) -> String {
+    let any = between(g, 1, max);
+    let length = *g.choose(&[1, max - 1, max, any]).unwrap();
+    text_of_length(g, length)
+}
+
+/// A string rejected by a field limited to `max` graphemes.
+fn invalid_text(g: &mut Gen, max: usize) -> String {
+    match between(g, 0, 4) {
+        0 => String::new(),
+        1 => (0..between(g, 1, 5))
+            .map(|_| *g.choose(&[' ', '\t', '\n']).unwrap())
+            .collect(),
+        2 => {
+            let length = between(g, max + 1, max * 2);
+            text_of_length(g, length)
+        }
+        // Over the limit by a single grapheme, made entirely of multibyte characters.
+        3 => (0..=max).map(|_| char_between(g, '一', '龥')).collect(),
+        _ => {
+            let text = text_of_length(g, max / 2);
+            let control = char_between(g, '\u{0}', '\u{8}');
+            format!("{text}{control}{text}")
+        }
+    }
+}
+
+impl Arbitrary for TicketTitle {
+    fn arbitrary(g: &mut Gen) -> Self {
+        valid_text(g, TITLE_MAX_LENGTH).try_into().unwrap()
+    }
+}
+
+impl Arbitrary for TicketDescription {
+    fn arbitrary(g: &mut Gen) -> Self {
+        valid_text(g, DESCRIPTION_MAX_LENGTH).try_into().unwrap()
+    }
+}
+
+impl Arbitrary for InvalidTitle {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self(invalid_text(g, TITLE_MAX_LENGTH))
+    }
+}
+
+impl Arbitrary for InvalidDescription {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self(invalid_text(g, DESCRIPTION_MAX_LENGTH))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    quickcheck! {
+        fn titles_round_trip(title: TicketTitle) -> bool {
+            TicketTitle::try_from(title.as_str()).is_ok_and(|parsed| parsed == title)
+        }
+
+        fn invalid_titles_are_rejected(title: InvalidTitle) -> bool {
+            TicketTitle::try_from(title.0).is_err()
+        }
+
+        fn descriptions_round_trip(description: TicketDescription) -> bool {
+            TicketDescription::try_from(description.as_str())
+                .is_ok_and(|parsed| parsed == description)
+        }
+
+        fn invalid_descriptions_are_rejected(description: InvalidDescription) -> bool {
+            TicketDescription::try_from(description.0).is_err()
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! `proptest` strategies producing valid and invalid ticket fields.
+//!
+//! Valid values are generated at every interesting length (a single character, one below the
+//! limit and exactly at the limit, plus anything in between) and mix ASCII with multibyte
+//! characters, so that limits are exercised in characters rather than bytes.
+use crate::policy::{DESCRIPTION_MAX_LENGTH, TITLE_MAX_LENGTH};
+use crate::{TicketDescription, TicketTitle};
+use proptest::prelude::*;
+
+/// A single, NFC-stable, non-whitespace grapheme cluster.
+fn grapheme() -> impl Strategy<Value = char> {
+    prop_oneof![
+        4 => proptest::char::range('!', '~'),
+        1 => proptest::char::range('À', 'ÿ'),
+        1 => proptest::char::range('一', '龥'),
+        1 => proptest::char::range('😀', '🙏'),
+    ]
+}
+
+/// A string of exactly `length` graphemes, without leading or trailing whitespace.
+fn text_of_length(length: usize) -> impl Strategy<Value = String> {
+    proptest::collection::vec((grapheme(), proptest::bool::weighted(0.15)), length).prop_map(
+        move |graphemes| {
+            graphemes
+                .into_iter()
+                .enumerate()
+                .map(|(i, (c, space))| {
+                    // Interior spaces count as a grapheme each, so they keep the length intact.
+                    if space && i != 0 && i != length - 1 {
+                        ' '
+                    } else {
+                        c
+                    }
+                })
+                .collect()
+        },
+    )
+}
+
+/// A length between 1 and `max`, biased towards the boundaries.
+fn valid_length(max: usize) -> impl Strategy<Value = usize> {
+    prop_oneof![Just(1), Just(max - 1), Just(max), 1..=max]
+}
+
+/// Strings that are rejected by a field limited to `max` graphemes.
+fn invalid_text(max: usize) -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        "[ \t\n]{1,5}",
+        (max + 1..=max * 2).prop_flat_map(text_of_length),
+        // Over the limit by a single grapheme, made entirely of multibyte characters.
+        proptest::collection::vec(proptest::char::range('一', '龥'), max + 1)
+            .prop_map(|chars| chars.into_iter().collect()),
+        (
+            text_of_length(max / 2),
+            proptest::char::range('\u{0}', '\u{8}')
+        )
+            .prop_map(|(text, control)| format!("{text}{control}{text}")),
+    ]
+}
+
+/// Raw strings accepted by `TicketTitle::try_from`.
+pub fn valid_title_strings() -> impl Strategy<Value = String> {
+    valid_length(TITLE_MAX_LENGTH).prop_flat_map(text_of_length)
+}
+
+/// Raw strings rejected by `TicketTitle::try_from`.
+pub fn invalid_title_strings() -> impl Strategy<Value = String> {
+    invalid_text(TITLE_MAX_LENGTH)
+}
+
+/// Raw strings accepted by `TicketDescription::try_from`.
+pub fn valid_description_strings() -> impl Strategy<Value = String> {
+    valid_length(DESCRIPTION_MAX_LENGTH).prop_flat_map(text_of_length)
+}
+
+/// Raw strings rejected by `TicketDescription::try_from`.
+pub fn invalid_description_strings() -> impl Strategy<Value = String> {
+    invalid_text(DESCRIPTION_MAX_LENGTH)
+}
+
+impl Arbitrary for TicketTitle {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        valid_title_strings()
+            .prop_map(|title| title.try_into().unwrap())
+            .boxed()
+    }
+}
+
+impl Arbitrary for TicketDescription {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        valid_description_strings()
+            .prop_map(|description| description.try_into().unwrap())
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn valid_titles_are_accepted(title in valid_title_strings()) {
+            prop_assert!(TicketTitle::try_from(title).is_ok());
+        }
+
+        #[test]
+        fn invalid_titles_are_rejected(title in invalid_title_strings()) {
+            prop_assert!(TicketTitle::try_from(title).is_err());
+        }
+
+        #[test]
+        fn valid_descriptions_are_accepted(description in valid_description_strings()) {
+            prop_assert!(TicketDescription::try_from(description).is_ok());
+        }
+
+        #[test]
+        fn invalid_descriptions_are_rejected(description in invalid_description_strings()) {
+            prop_assert!(TicketDescription::try_from(description).is_err());
+        }
+    }
+}
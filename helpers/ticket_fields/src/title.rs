@@ -1,22 +1,77 @@
+use crate::policy::{FieldPolicy, PolicyViolation};
+use crate::validation::LengthUnit;
 use std::convert::TryFrom;
 
 #[derive(Debug, PartialEq, Clone, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub struct TicketTitle(String);
 
 #[derive(Debug, thiserror::Error)]
 pub enum TicketTitleError {
     #[error("The title cannot be empty")]
     Empty,
-    #[error("The title cannot be longer than 50 bytes")]
-    TooLong,
+    #[error("The title must be at least {min} {unit} long, but it is {length} {unit} long")]
+    TooShort {
+        length: usize,
+        min: usize,
+        unit: LengthUnit,
+    },
+    #[error("The title cannot be longer than {max} {unit}, but it is {length} {unit} long")]
+    TooLong {
+        length: usize,
+        max: usize,
+        unit: LengthUnit,
+    },
+    #[error("The title cannot contain control characters (found {0:?})")]
+    ControlCharacter(char),
+    #[error("The title cannot contain {0:?}")]
+    DisallowedCharacter(char),
+    #[error("The title cannot contain the word {0:?}")]
+    ForbiddenWord(String),
+    #[error("The title cannot match the pattern {0:?}")]
+    ForbiddenPattern(String),
+}
+
+impl From<PolicyViolation> for TicketTitleError {
+    fn from(violation: PolicyViolation) -> Self {
+        match violation {
+            PolicyViolation::Empty => Self::Empty,
+            PolicyViolation::TooShort { length, min, unit } => Self::TooShort { length, min, unit },
+            PolicyViolation::TooLong { length, max, unit } => Self::TooLong { length, max, unit },
+            PolicyViolation::ControlCharacter(c) => Self::ControlCharacter(c),
+            PolicyViolation::DisallowedCharacter(c) => Self::DisallowedCharacter(c),
+            PolicyViolation::ForbiddenWord(word) => Self::ForbiddenWord(word),
+            PolicyViolation::ForbiddenPattern(pattern) => Self::ForbiddenPattern(pattern),
+        }
+    }
+}
+
+impl TicketTitle {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Validate `value` against a custom [`FieldPolicy`] rather than the default one.
+    pub fn parse_with(policy: &FieldPolicy, value: &str) -> Result<Self, TicketTitleError> {
+        Ok(Self(policy.check(value)?))
+    }
 }
 
 impl TryFrom<String> for TicketTitle {
     type Error = TicketTitleError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        validate(&value)?;
-        Ok(Self(value))
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<TicketTitle> for String {
+    fn from(value: TicketTitle) -> Self {
+        value.0
     }
 }
 
@@ -24,18 +79,7 @@ impl TryFrom<&str> for TicketTitle {
     type Error = TicketTitleError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        validate(value)?;
-        Ok(Self(value.to_string()))
-    }
-}
-
-fn validate(title: &str) -> Result<(), TicketTitleError> {
-    if title.is_empty() {
-        Err(TicketTitleError::Empty)
-    } else if title.len() > 50 {
-        Err(TicketTitleError::TooLong)
-    } else {
-        Ok(())
+        Self::parse_with(&FieldPolicy::title(), value)
     }
 }
 
@@ -61,7 +105,45 @@ mod tests {
     #[test]
     fn test_try_from_long_string() {
         let err = TicketTitle::try_from(overly_long_title()).unwrap_err();
-        assert_eq!(err.to_string(), "The title cannot be longer than 50 bytes");
+        assert_eq!(
+            err.to_string(),
+            "The title cannot be longer than 50 characters, but it is 84 characters long"
+        );
+    }
+
+    #[test]
+    fn test_try_from_multibyte_string() {
+        // 50 characters, but 150 bytes.
+        let input = "漢".repeat(50);
+        let title = TicketTitle::try_from(input.clone()).unwrap();
+        assert_eq!(title.0, input);
+
+        let err = TicketTitle::try_from("🦀".repeat(51)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The title cannot be longer than 50 characters, but it is 51 characters long"
+        );
+    }
+
+    #[test]
+    fn test_try_from_normalizes() {
+        let title = TicketTitle::try_from("  Cafe\u{301}  ").unwrap();
+        assert_eq!(title.0, "Caf\u{e9}");
+    }
+
+    #[test]
+    fn test_try_from_whitespace_only() {
+        let err = TicketTitle::try_from(" \t ").unwrap_err();
+        assert_eq!(err.to_string(), "The title cannot be empty");
+    }
+
+    #[test]
+    fn test_try_from_control_character() {
+        let err = TicketTitle::try_from("A\ntitle").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The title cannot contain control characters (found '\\n')"
+        );
     }
 
     #[test]
@@ -69,4 +151,39 @@ mod tests {
         let title = TicketTitle::try_from("A title").unwrap();
         assert_eq!(title.0, "A title");
     }
+
+    #[test]
+    fn test_parse_with_policy() {
+        let policy = FieldPolicy::new(3, 10).unwrap().forbid_word("asap");
+        let title = TicketTitle::parse_with(&policy, "Fix login").unwrap();
+        assert_eq!(title.0, "Fix login");
+
+        let err = TicketTitle::parse_with(&policy, "Go").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The title must be at least 3 characters long, but it is 2 characters long"
+        );
+
+        let err = TicketTitle::parse_with(&policy, "Fix ASAP").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The title cannot contain the word \"asap\""
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let title = TicketTitle::try_from("A title").unwrap();
+        let json = serde_json::to_string(&title).unwrap();
+        assert_eq!(json, "\"A title\"");
+        assert_eq!(serde_json::from_str::<TicketTitle>(&json).unwrap(), title);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_invalid() {
+        let err = serde_json::from_str::<TicketTitle>("\"\"").unwrap_err();
+        assert_eq!(err.to_string(), "The title cannot be empty");
+    }
 }
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The maximum length of a [`UserId`], in ASCII characters.
+const USER_ID_MAX_LENGTH: usize = 32;
+
+/// A user handle, such as `alice` or `bob.smith`.
+///
+/// Handles are 1 to 32 lowercase ASCII letters, digits, `.`, `-` or `_`, starting with a
+/// letter. Whether the user actually exists is up to whoever keeps the user directory.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct UserId(String);
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum UserIdError {
+    #[error("The user id cannot be empty")]
+    Empty,
+    #[error("The user id cannot be longer than {USER_ID_MAX_LENGTH} characters")]
+    TooLong,
+    #[error("The user id must start with a lowercase letter")]
+    MustStartWithLetter,
+    #[error(
+        "The user id can only contain lowercase letters, digits, '.', '-' and '_' (found {0:?})"
+    )]
+    InvalidCharacter(char),
+}
+
+impl UserId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<&str> for UserId {
+    type Error = UserIdError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let first = value.chars().next().ok_or(UserIdError::Empty)?;
+        if !first.is_ascii_lowercase() {
+            return Err(UserIdError::MustStartWithLetter);
+        }
+        if let Some(c) = value.chars().find(|c| {
+            !c.is_ascii_lowercase() && !c.is_ascii_digit() && !matches!(c, '.' | '-' | '_')
+        }) {
+            return Err(UserIdError::InvalidCharacter(c));
+        }
+        if value.len() > USER_ID_MAX_LENGTH {
+            return Err(UserIdError::TooLong);
+        }
+        Ok(Self(value.to_string()))
+    }
+}
+
+impl TryFrom<String> for UserId {
+    type Error = UserIdError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl FromStr for UserId {
+    type Err = UserIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl From<UserId> for String {
+    fn from(value: UserId) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for UserId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for UserId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UserId({})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid() {
+        for id in ["alice", "bob.smith", "carol-2", "d_e"] {
+            assert_eq!(id.parse::<UserId>().unwrap().as_str(), id);
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!("".parse::<UserId>(), Err(UserIdError::Empty));
+        assert_eq!(
+            "Alice".parse::<UserId>(),
+            Err(UserIdError::MustStartWithLetter)
+        );
+        assert_eq!(
+            "2pac".parse::<UserId>(),
+            Err(UserIdError::MustStartWithLetter)
+        );
+        assert_eq!(
+            "bob smith".parse::<UserId>(),
+            Err(UserIdError::InvalidCharacter(' '))
+        );
+        assert_eq!("a".repeat(33).parse::<UserId>(), Err(UserIdError::TooLong));
+        assert_eq!(
+            UserIdError::TooLong.to_string(),
+            "The user id cannot be longer than 32 characters"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How the length of a text field is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthUnit {
+    /// UTF-8 encoded bytes.
+    Bytes,
+    /// Unicode scalar values (`char`s).
+    Chars,
+    /// Extended grapheme clusters, i.e. what a user perceives as a single character.
+    Graphemes,
+}
+
+impl LengthUnit {
+    /// Measure the length of `text` in this unit.
+    pub fn measure(self, text: &str) -> usize {
+        match self {
+            LengthUnit::Bytes => text.len(),
+            LengthUnit::Chars => text.chars().count(),
+            LengthUnit::Graphemes => text.graphemes(true).count(),
+        }
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LengthUnit::Bytes => write!(f, "bytes"),
+            LengthUnit::Chars => write!(f, "code points"),
+            LengthUnit::Graphemes => write!(f, "characters"),
+        }
+    }
+}
+
+/// NFC-normalize `text` and strip leading/trailing whitespace.
+pub(crate) fn normalize(text: &str) -> String {
+    text.nfc().collect::<String>().trim().to_string()
+}
+
+/// Return the first control character in `text`, if any.
+///
+/// Line breaks and tabs are only tolerated when `allow_line_breaks` is set.
+pub(crate) fn find_control_char(text: &str, allow_line_breaks: bool) -> Option<char> {
+    text.chars()
+        .find(|&c| c.is_control() && !(allow_line_breaks && matches!(c, '\n' | '\r' | '\t')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let text = "e\u{301}🦀";
+        assert_eq!(LengthUnit::Bytes.measure(text), 7);
+        assert_eq!(LengthUnit::Chars.measure(text), 3);
+        assert_eq!(LengthUnit::Graphemes.measure(text), 2);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  e\u{301} \n"), "\u{e9}");
+    }
+
+    #[test]
+    fn test_find_control_char() {
+        assert_eq!(find_control_char("a\nb", false), Some('\n'));
+        assert_eq!(find_control_char("a\nb", true), None);
+        assert_eq!(find_control_char("a\u{7}b", true), Some('\u{7}'));
+    }
+}
@@ -0,0 +1,207 @@
+//! Checks that every [`TicketRepository`] must pass.
+//!
+//! Each check takes a fresh, empty repository. Backends usually run them all through
+//! [`conformance_tests!`](crate::conformance_tests), which generates one `#[test]` per check:
+//!
+//! ```ignore
+//! ticket_store::conformance_tests!(my_backend, MyRepository::open(temp_dir()));
+//! ```
+use crate::{Status, Ticket, TicketDraft, TicketId, TicketRepository, TicketStore};
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
+
+fn ticket(number: u64) -> Ticket {
+    Ticket::new(TicketId::new(number), draft())
+}
+
+pub fn add_and_get(repository: impl TicketRepository) {
+    let mut store = TicketStore::with_repository(repository);
+    let id = store.add_ticket(draft());
+
+    let ticket = store.get(id).expect("the ticket was just added");
+    assert_eq!(ticket.id(), id);
+    assert_eq!(ticket.title, ticket_title());
+    assert_eq!(ticket.description, ticket_description());
+    assert_eq!(ticket.status, Status::ToDo);
+    assert_eq!(&store[id], ticket);
+}
+
+pub fn ids_are_unique(repository: impl TicketRepository) {
+    let mut store = TicketStore::with_repository(repository);
+    let ids: Vec<TicketId> = (0..10).map(|_| store.add_ticket(draft())).collect();
+
+    for (i, id) in ids.iter().enumerate() {
+        assert!(!ids[..i].contains(id), "{id} was handed out twice");
+        assert_eq!(store[id].id(), *id);
+    }
+    assert_eq!(store.repository().len(), ids.len());
+}
+
+pub fn missing_ids_are_none(repository: impl TicketRepository) {
+    let mut store = TicketStore::with_repository(repository);
+    assert!(store.repository().is_empty());
+    assert!(store.get(TicketId::new(0)).is_none());
+
+    let id = store.add_ticket(draft());
+    assert!(store.get(TicketId::new(id.number() + 1)).is_none());
+    assert!(store.get_mut(TicketId::new(id.number() + 1)).is_none());
+}
+
+pub fn edits_are_kept(repository: impl TicketRepository) {
+    let mut store = TicketStore::with_repository(repository);
+    let first = store.add_ticket(draft());
+    let second = store.add_ticket(draft());
+
+    store[first].status = Status::InProgress;
+    store.get_mut(second).unwrap().title = "Edited".try_into().unwrap();
+
+    assert_eq!(store[first].status, Status::InProgress);
+    assert_eq!(store[first].title, ticket_title());
+    assert_eq!(store[second].title.as_str(), "Edited");
+    assert_eq!(store[second].status, Status::ToDo);
+}
+
+pub fn insert_replaces_same_id(mut repository: impl TicketRepository) {
+    let ticket = ticket(7);
+    repository.insert(ticket.clone());
+    repository.insert(Ticket {
+        status: Status::Done,
+        ..ticket
+    });
+
+    assert_eq!(repository.len(), 1);
+    assert_eq!(
+        repository.get(TicketId::new(7)).unwrap().status,
+        Status::Done
+    );
+}
+
+pub fn resumes_after_existing_tickets(repository: impl TicketRepository) {
+    let mut store = TicketStore::with_repository(repository);
+    let first = store.add_ticket(draft());
+
+    let TicketStore { repository, .. } = store;
+    let mut store = TicketStore::with_repository(repository);
+    let second = store.add_ticket(draft());
+    assert_ne!(first, second);
+    assert_eq!(store.repository().len(), 2);
+}
+
+pub fn resumes_after_the_highest_id(mut repository: impl TicketRepository) {
+    // As left behind by deletions: the next id isn't the number of tickets.
+    repository.insert(ticket(0));
+    repository.insert(ticket(2));
+    let mut store = TicketStore::with_repository(repository);
+    store[TicketId::new(2)].status = Status::Done;
+
+    assert_eq!(store.add_ticket(draft()), TicketId::new(3));
+    assert_eq!(store[TicketId::new(2)].status, Status::Done);
+    assert_eq!(store.repository().len(), 3);
+}
+
+pub fn remove_and_iter(mut repository: impl TicketRepository) {
+    for number in [2, 0, 1] {
+        repository.insert(ticket(number));
+    }
+    assert_eq!(
+        repository.remove(TicketId::new(0)).unwrap().id(),
+        TicketId::new(0)
+    );
+    assert!(repository.remove(TicketId::new(0)).is_none());
+
+    let mut ids: Vec<TicketId> = repository.iter().map(|t| t.id()).collect();
+    ids.sort();
+    assert_eq!(ids, vec![TicketId::new(1), TicketId::new(2)]);
+    assert_eq!(repository.len(), 2);
+}
+
+pub fn archived_tickets_are_hidden(repository: impl TicketRepository) {
+    let mut store = TicketStore::with_repository(repository);
+    let kept = store.add_ticket(draft());
+    let archived = store.add_ticket(draft());
+
+    assert!(store.archive(archived));
+    assert!(!store.archive(archived));
+    assert!(store.get(archived).is_none());
+    assert_eq!(store.get_archived(archived).unwrap().id(), archived);
+    assert_eq!(store.iter().map(|t| t.id()).collect::<Vec<_>>(), vec![kept]);
+
+    assert!(store.restore(archived));
+    assert!(store.get_archived(archived).is_none());
+    assert_eq!(store[archived].id(), archived);
+}
+
+pub fn index_panics_on_missing_id(repository: impl TicketRepository) {
+    let store = TicketStore::with_repository(repository);
+    let _ = &store[TicketId::new(0)];
+}
+
+/// Generate a module named `$name`, with one test per conformance check.
+///
+/// `$repository` is evaluated once per test, and must produce an empty repository.
+#[macro_export]
+macro_rules! conformance_tests {
+    ($name:ident, $repository:expr) => {
+        mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #[test]
+            fn add_and_get() {
+                $crate::conformance::add_and_get($repository);
+            }
+
+            #[test]
+            fn ids_are_unique() {
+                $crate::conformance::ids_are_unique($repository);
+            }
+
+            #[test]
+            fn missing_ids_are_none() {
+                $crate::conformance::missing_ids_are_none($repository);
+            }
+
+            #[test]
+            fn edits_are_kept() {
+                $crate::conformance::edits_are_kept($repository);
+            }
+
+            #[test]
+            fn insert_replaces_same_id() {
+                $crate::conformance::insert_replaces_same_id($repository);
+            }
+
+            #[test]
+            fn resumes_after_existing_tickets() {
+                $crate::conformance::resumes_after_existing_tickets($repository);
+            }
+
+            #[test]
+            fn resumes_after_the_highest_id() {
+                $crate::conformance::resumes_after_the_highest_id($repository);
+            }
+
+            #[test]
+            fn remove_and_iter() {
+                $crate::conformance::remove_and_iter($repository);
+            }
+
+            #[test]
+            fn archived_tickets_are_hidden() {
+                $crate::conformance::archived_tickets_are_hidden($repository);
+            }
+
+            #[test]
+            #[should_panic(expected = "There is no ticket with id TKT-0")]
+            fn index_panics_on_missing_id() {
+                $crate::conformance::index_panics_on_missing_id($repository);
+            }
+        }
+    };
+}
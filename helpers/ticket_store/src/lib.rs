@@ -0,0 +1,226 @@
+//! A ticket store that is generic over where the tickets are kept.
+//!
+//! [`TicketStore`] hands out ids, archives and removes tickets, and implements
+//! `Index`/`IndexMut` once, on top of any [`TicketRepository`]. In-memory repositories
+//! backed by a `Vec`, a `HashMap` and a `BTreeMap` ship in [`memory`]; new backends can
+//! check themselves against the [`conformance`] suite.
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Index, IndexMut};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+pub use memory::{BTreeMapRepository, HashMapRepository, VecRepository};
+pub use ticket_fields::TicketId;
+
+pub mod conformance;
+pub mod memory;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ticket {
+    // Not public: the store finds tickets by id, so a ticket borrowed through `get_mut` or
+    // `IndexMut` must not be able to move to another one.
+    id: TicketId,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    pub status: Status,
+    pub assignee: Option<String>,
+}
+
+impl Ticket {
+    /// A ticket to do, and unassigned, e.g. for a repository loading tickets it already
+    /// numbered.
+    pub fn new(id: TicketId, draft: TicketDraft) -> Self {
+        Self {
+            id,
+            title: draft.title,
+            description: draft.description,
+            status: Status::ToDo,
+            assignee: None,
+        }
+    }
+
+    /// The id can be read, but not reassigned, through a mutable borrow:
+    ///
+    /// ```compile_fail
+    /// use ticket_store::{Ticket, TicketId};
+    ///
+    /// fn renumber(ticket: &mut Ticket, id: TicketId) {
+    ///     ticket.id = id;
+    /// }
+    /// ```
+    pub fn id(&self) -> TicketId {
+        self.id
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TicketDraft {
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+/// Where a [`TicketStore`] keeps its tickets.
+pub trait TicketRepository {
+    /// Store `ticket`, replacing any ticket with the same id.
+    fn insert(&mut self, ticket: Ticket);
+
+    fn get(&self, id: TicketId) -> Option<&Ticket>;
+
+    fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket>;
+
+    fn remove(&mut self, id: TicketId) -> Option<Ticket>;
+
+    /// Every ticket stored, in an order of the repository's choosing.
+    fn iter(&self) -> impl Iterator<Item = &Ticket>;
+
+    /// The number of tickets stored.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TicketStore<R> {
+    repository: R,
+    // Hidden from iteration and lookups, but still readable through `get_archived`.
+    archived: BTreeMap<TicketId, Ticket>,
+    // Ids of removed tickets, so that lookups can tell them apart from ids never issued.
+    deleted: BTreeSet<TicketId>,
+    counter: u64,
+}
+
+impl<R: TicketRepository + Default> TicketStore<R> {
+    pub fn new() -> Self {
+        Self::with_repository(R::default())
+    }
+}
+
+impl<R: TicketRepository> TicketStore<R> {
+    /// A store on top of a repository that may already hold tickets.
+    ///
+    /// New tickets are numbered after the highest id already stored: the repository may have
+    /// gaps, e.g. if tickets were deleted before it was loaded.
+    pub fn with_repository(repository: R) -> Self {
+        let counter = repository
+            .iter()
+            .map(|ticket| ticket.id().number() + 1)
+            .max()
+            .unwrap_or(0);
+        Self {
+            repository,
+            archived: BTreeMap::new(),
+            deleted: BTreeSet::new(),
+            counter,
+        }
+    }
+
+    pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
+        let id = TicketId::new(self.counter);
+        self.counter += 1;
+        self.repository.insert(Ticket::new(id, ticket));
+        id
+    }
+
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.repository.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
+        self.repository.get_mut(id)
+    }
+
+    /// Delete a ticket for good, whether it is archived or not.
+    pub fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        let ticket = self
+            .repository
+            .remove(id)
+            .or_else(|| self.archived.remove(&id))?;
+        self.deleted.insert(id);
+        Some(ticket)
+    }
+
+    /// Hide a ticket from lookups and iteration, without deleting it.
+    ///
+    /// Returns `false` if there is no such ticket, or if it is already archived.
+    pub fn archive(&mut self, id: TicketId) -> bool {
+        let Some(ticket) = self.repository.remove(id) else {
+            return false;
+        };
+        self.archived.insert(id, ticket);
+        true
+    }
+
+    /// Bring an archived ticket back.
+    pub fn restore(&mut self, id: TicketId) -> bool {
+        let Some(ticket) = self.archived.remove(&id) else {
+            return false;
+        };
+        self.repository.insert(ticket);
+        true
+    }
+
+    pub fn get_archived(&self, id: TicketId) -> Option<&Ticket> {
+        self.archived.get(&id)
+    }
+
+    /// The tickets that are neither archived nor deleted, in the repository's order.
+    pub fn iter(&self) -> impl Iterator<Item = &Ticket> {
+        self.repository.iter()
+    }
+
+    /// Where the tickets that are neither archived nor deleted are kept.
+    pub fn repository(&self) -> &R {
+        &self.repository
+    }
+
+    /// Explain why there is no ticket for `id`, for the panics of `Index` and `IndexMut`.
+    fn missing(&self, id: TicketId) -> String {
+        if self.deleted.contains(&id) {
+            format!("Ticket {id} has been deleted")
+        } else if self.archived.contains_key(&id) {
+            format!("Ticket {id} is archived, use `get_archived` to read it")
+        } else {
+            format!("There is no ticket with id {id}")
+        }
+    }
+}
+
+impl<R: TicketRepository> Index<TicketId> for TicketStore<R> {
+    type Output = Ticket;
+
+    fn index(&self, index: TicketId) -> &Self::Output {
+        self.get(index)
+            .unwrap_or_else(|| panic!("{}", self.missing(index)))
+    }
+}
+
+impl<R: TicketRepository> Index<&TicketId> for TicketStore<R> {
+    type Output = Ticket;
+
+    fn index(&self, index: &TicketId) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl<R: TicketRepository> IndexMut<TicketId> for TicketStore<R> {
+    fn index_mut(&mut self, index: TicketId) -> &mut Self::Output {
+        if self.get(index).is_none() {
+            panic!("{}", self.missing(index));
+        }
+        self.get_mut(index).unwrap()
+    }
+}
+
+impl<R: TicketRepository> IndexMut<&TicketId> for TicketStore<R> {
+    fn index_mut(&mut self, index: &TicketId) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
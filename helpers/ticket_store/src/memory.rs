@@ -0,0 +1,124 @@
+//! Repositories that keep tickets in memory.
+use crate::{Ticket, TicketId, TicketRepository};
+use std::collections::{btree_map, BTreeMap, HashMap};
+use std::ops::RangeBounds;
+
+/// Lookups are linear, but tickets are kept in id order.
+#[derive(Clone, Debug, Default)]
+pub struct VecRepository(Vec<Ticket>);
+
+#[derive(Clone, Debug, Default)]
+pub struct HashMapRepository(HashMap<TicketId, Ticket>);
+
+#[derive(Clone, Debug, Default)]
+pub struct BTreeMapRepository(BTreeMap<TicketId, Ticket>);
+
+impl TicketRepository for VecRepository {
+    fn insert(&mut self, ticket: Ticket) {
+        match self.get_mut(ticket.id()) {
+            Some(existing) => *existing = ticket,
+            None => {
+                let position = self.0.partition_point(|t| t.id() < ticket.id());
+                self.0.insert(position, ticket);
+            }
+        }
+    }
+
+    fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.0.iter().find(|t| t.id() == id)
+    }
+
+    fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
+        self.0.iter_mut().find(|t| t.id() == id)
+    }
+
+    fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        let position = self.0.iter().position(|t| t.id() == id)?;
+        Some(self.0.remove(position))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Ticket> {
+        self.0.iter()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl TicketRepository for HashMapRepository {
+    fn insert(&mut self, ticket: Ticket) {
+        self.0.insert(ticket.id(), ticket);
+    }
+
+    fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.0.get(&id)
+    }
+
+    fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
+        self.0.get_mut(&id)
+    }
+
+    fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        self.0.remove(&id)
+    }
+
+    /// In no particular order.
+    fn iter(&self) -> impl Iterator<Item = &Ticket> {
+        self.0.values()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl BTreeMapRepository {
+    /// The tickets with an id in `range`, ordered by id.
+    pub fn range(
+        &self,
+        range: impl RangeBounds<TicketId>,
+    ) -> btree_map::Range<'_, TicketId, Ticket> {
+        self.0.range(range)
+    }
+
+    /// Every ticket, ordered by id.
+    pub fn values(&self) -> btree_map::Values<'_, TicketId, Ticket> {
+        self.0.values()
+    }
+}
+
+impl TicketRepository for BTreeMapRepository {
+    fn insert(&mut self, ticket: Ticket) {
+        self.0.insert(ticket.id(), ticket);
+    }
+
+    fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.0.get(&id)
+    }
+
+    fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
+        self.0.get_mut(&id)
+    }
+
+    fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        self.0.remove(&id)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Ticket> {
+        self.values()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::conformance_tests!(vec, VecRepository::default());
+    crate::conformance_tests!(hash_map, HashMapRepository::default());
+    crate::conformance_tests!(btree_map, BTreeMapRepository::default());
+}